@@ -0,0 +1,14 @@
+fn main() {
+    let mut i = 0;
+    loop {
+        let s = string_from("looped");
+        i = i + 1;
+        if i == 3 {
+            break;
+        }
+        if i == 1 {
+            continue;
+        }
+        println("{}", s);
+    }
+}
@@ -0,0 +1,17 @@
+fn take(s : String) {
+    println("took {}", s);
+}
+
+fn maybe_take(is_taken : bool) {
+    let s = string_from("maybe");
+    if is_taken {
+        take(s);
+    } else {
+        println("kept");
+    }
+}
+
+fn main() {
+    maybe_take(true);
+    maybe_take(false);
+}
@@ -0,0 +1,16 @@
+fn measure(is_early : bool) -> i64 {
+    let outer = string_from("outer");
+    loop {
+        let inner = string_from("inner");
+        if is_early {
+            let kept = string_from("kept");
+            return len(kept);
+        }
+        break;
+    }
+    len(outer)
+}
+
+fn main() {
+    println("{} {}", measure(true), measure(false));
+}
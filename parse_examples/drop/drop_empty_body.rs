@@ -0,0 +1,5 @@
+fn ignore(s : String) {}
+
+fn main() {
+    ignore(string_from("ignored"));
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let s = string_from("a");
+    let t = s; println("{}", t);
+}
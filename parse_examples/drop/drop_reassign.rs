@@ -0,0 +1,5 @@
+fn main() {
+    let mut s = string_from("first");
+    s = string_from("second");
+    println("{}", s);
+}
@@ -0,0 +1,3 @@
+fn main() {
+    let s = string_from("a"); let t = string_from("b"); println("{} {}", s, t);
+}
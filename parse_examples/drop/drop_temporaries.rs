@@ -0,0 +1,11 @@
+fn make(contents : &str) -> String {
+    string_from(contents)
+}
+
+fn main() {
+    let n = len(string_from("t"));
+    make("discarded");
+    println("{} {}", n, make("printed"));
+    let m = { len(make("block")) } + 1;
+    println("{}", m);
+}
@@ -0,0 +1,25 @@
+fn sign(n : i64) -> i64 {
+    if n < 0 {
+        return -1;
+    }
+    if n == 0 {
+        return 0;
+    } else {
+        ()
+    }
+    1
+}
+
+fn first_over(limit : i64) -> i64 {
+    let mut n = 0;
+    loop {
+        n += 1;
+        if n * n > limit {
+            return n;
+        }
+    }
+}
+
+fn main() {
+    println("{} {} {} {}", sign(-5), sign(0), sign(5), first_over(50));
+}
@@ -0,0 +1,10 @@
+fn sign(n : i64) -> i64 {
+    if n < 0 {
+        return false;
+    }
+    1
+}
+
+fn main() {
+    println("{}", sign(-5));
+}
@@ -0,0 +1,40 @@
+fn main() {
+    let mut counter = 0;
+    let result = loop {
+        counter = counter + 1;
+        if counter == 10 {
+            break counter * 2;
+        }
+    };
+    println("The result is {}", result);
+
+    let mut count = 0;
+    'counting_up: loop {
+        let mut remaining = 10;
+        loop {
+            if remaining == 9 {
+                break;
+            }
+            if count == 2 {
+                break 'counting_up;
+            }
+            remaining = remaining - 1;
+        }
+        count = count + 1;
+    }
+    println("End count = {}", count);
+
+    let mut number = 3;
+    while number != 0 {
+        number = number - 1;
+        if number == 1 {
+            continue;
+        }
+        println("{}!", number);
+    }
+
+    let greeting = loop {
+        break string_from("hello");
+    };
+    println("{}", greeting);
+}
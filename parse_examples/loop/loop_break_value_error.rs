@@ -0,0 +1,12 @@
+fn main() {
+    let x = loop {
+        if true {
+            break 1;
+        }
+        break true;
+    };
+    while true {
+        break 5;
+    }
+    break;
+}
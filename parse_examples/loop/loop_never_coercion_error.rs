@@ -0,0 +1,5 @@
+fn main() {
+    let b = false;
+    let x = if b { loop {} } else { string_from("a") };
+    let y : i64 = x;
+}
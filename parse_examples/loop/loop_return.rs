@@ -0,0 +1,34 @@
+fn from_loop() -> i64 {
+    loop {
+        return 7;
+    }
+}
+
+fn from_while() -> i64 {
+    let mut count = 0;
+    while count < 10 {
+        count += 1;
+        return count * 8;
+    }
+    0
+}
+
+fn from_nested_block() -> i64 {
+    let x = 9;
+    {
+        let y = 0;
+        {
+            return x + y;
+        }
+    }
+}
+
+fn main() {
+    let mut total = 0;
+    let mut count = 0;
+    while count < 3 {
+        total += from_loop() + from_while() + from_nested_block();
+        count += 1;
+    }
+    println("{} {} {} {}", from_loop(), from_while(), from_nested_block(), total);
+}
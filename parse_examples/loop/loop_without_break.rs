@@ -0,0 +1,15 @@
+fn forever() -> i64 {
+    loop { }
+}
+
+fn leave_outer() -> i64 {
+    'outer: loop {
+        let s : String = loop {
+            break 'outer 4;
+        };
+    }
+}
+
+fn main() {
+    println("{}", leave_outer());
+}
@@ -0,0 +1,7 @@
+fn forever() -> i64 {
+    while true { }
+}
+
+fn main() {
+    println("{}", forever());
+}
@@ -0,0 +1,7 @@
+fn main() {
+    let s = string_from("a");
+    loop {
+        let t = s;
+        println("{}", t);
+    }
+}
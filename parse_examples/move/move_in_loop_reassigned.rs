@@ -0,0 +1,13 @@
+fn take(s : String) {
+    println("took {}", s);
+}
+
+fn main() {
+    let mut s = string_from("first");
+    let mut i = 0;
+    while i < 2 {
+        take(s);
+        s = string_from("again");
+        i = i + 1;
+    }
+}
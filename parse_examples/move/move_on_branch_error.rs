@@ -0,0 +1,9 @@
+fn take(s : String) {}
+
+fn main() {
+    let s = string_from("a");
+    if true {
+        take(s);
+    }
+    println("{}", s);
+}
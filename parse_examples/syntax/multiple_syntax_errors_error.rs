@@ -0,0 +1,18 @@
+fn main() {
+    let x = 5 +;
+    let y : i64 = x * 2;
+    if x > 2 {
+        println("{}", x)
+        let z = ;
+    }
+    println("{}", y);
+}
+
+fn other() -> i64 {
+    let a = 1
+    a
+}
+
+fn mismatched() -> i64 {
+    true
+}
@@ -0,0 +1,7 @@
+fn main() {
+    let x : i64;
+    if true {
+        x = 1;
+    }
+    println("{}", x);
+}
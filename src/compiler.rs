@@ -1,8 +1,9 @@
 #[allow(dead_code)]
 pub mod instructions;
 pub mod error;
+pub mod bytecode;
 
-use std::collections::{HashMap, LinkedList};
+use std::collections::LinkedList;
 use crate::parser::ast::{
     Block,
     Expr,
@@ -11,24 +12,42 @@ use crate::parser::ast::{
     UnaryOperator,
     BinaryOperator,
     VariadicOperator,
+    PrimitiveOperator,
     Stmt,
     SequenceStmt,
-    SourceLocation
+    Span,
+    AST,
+    binding_name,
+    is_explicit_return,
+    source_name,
 };
+use crate::static_checker::ExpiredLifetimes;
 use instructions::Instruction;
 use error::Error;
 
 type Result<T> = std::result::Result<T, Error>;
 type CompileResult = Result<Vec<Instruction>>;
-type ExpiredLifetimes = HashMap<usize, Vec<String>>;
 type IndexTable = LinkedList<(String, usize)>;
 
+// Tracks the number of runtime frames entered within the function being compiled, along with
+// the label and frame count of each enclosing loop, so that break and continue know how many
+// frames to unwind.
+#[derive(Default)]
+pub struct LoopTable {
+    num_of_frames: usize,
+    loops: LinkedList<(Option<String>, usize)>,
+}
+
 pub fn compile(ast: &Vec<Stmt>, drop_at: &ExpiredLifetimes) -> CompileResult {
     let mut index_table: IndexTable = LinkedList::new();
+    let mut loop_table = LoopTable::default();
     let mut has_main_function = false;
     let mut main_function_index = 0;
 
-    scan_declaration_names(ast)?
+    let top_level_names = scan_declaration_names(ast)?;
+    let num_of_top_level_names = top_level_names.len();
+
+    top_level_names
         .into_iter()
         .enumerate()
         .for_each(|(index, name)| {
@@ -43,22 +62,40 @@ pub fn compile(ast: &Vec<Stmt>, drop_at: &ExpiredLifetimes) -> CompileResult {
         return Ok(vec![Instruction::START, Instruction::DONE])
     }
 
-    let mut bytecode = ast.iter()
-        .map(|stmt| compile_top_level(stmt, drop_at, &mut index_table))
-        .fold(Ok(vec![Instruction::START]), accumulate_bytecode)?;
-    bytecode.extend(vec![
+    // The top-level declarations live in their own frame, which returns the value of main.
+    let mut top_level_bytecode = ast.iter()
+        .map(|stmt| compile_top_level(stmt, drop_at, &mut index_table, &mut loop_table))
+        .fold(Ok(vec![]), accumulate_bytecode)?;
+    top_level_bytecode.extend(vec![
         Instruction::LD(main_function_index),
         Instruction::CALL(0),
-        Instruction::DONE]);
+        Instruction::RTN]);
+
+    let mut bytecode = vec![Instruction::START];
+    bytecode.extend(compile_frame(top_level_bytecode, num_of_top_level_names));
+    bytecode.push(Instruction::DONE);
 
     Ok(bytecode)
 }
 
+// Wraps the given body in a closure that is called immediately, so that the body runs in a
+// new environment frame with room for the given number of declarations. The body must end
+// with a RTN, which resumes execution at the GOTOR that skips over the body.
+fn compile_frame(body_bytecode: Vec<Instruction>, num_of_declarations: usize) -> Vec<Instruction> {
+    let mut bytecode = vec![
+        Instruction::LDF(0, 3, num_of_declarations),
+        Instruction::CALL(0),
+        Instruction::GOTOR(body_bytecode.len() + 1),
+    ];
+    bytecode.extend(body_bytecode);
+    bytecode
+}
+
 fn scan_declaration_names(stmts: &Vec<Stmt>) -> Result<Vec<String>> {
     let scan_stmt = |stmt: &Stmt| match stmt {
         Stmt::LetStmt { name, .. } => {
-            let name = get_identifier_name(name)?;
-            Ok(vec![name])
+            let binding = binding_name(&get_identifier_name(name)?, name.get_source_location());
+            Ok(vec![pending_binding(&binding)])
         },
         Stmt::FuncDeclaration { name, .. } => {
             let name = get_identifier_name(name)?;
@@ -90,6 +127,27 @@ fn scan_declaration_names_from_block(block: &Block) -> Result<Vec<String>> {
     scan_declaration_names(&stmts_in_block)
 }
 
+// A variable declared by a let statement has its slot from the start of its block, so that shadowed
+// variables keep theirs. Its name is only bound once its value is computed, as the value still
+// sees any variable of the same name declared before.
+fn pending_binding(binding: &str) -> String {
+    format!("let {}", binding)
+}
+
+fn bind_pending(index_table: &mut IndexTable, binding: &str, position: Span) -> Result<usize> {
+    let pending = pending_binding(binding);
+    for (name, index) in index_table.iter_mut() {
+        if *name == pending {
+            *name = String::from(binding);
+            return Ok(*index)
+        }
+    }
+    Err(Error {
+        message: format!("The name \"{}\" is not found", source_name(binding)),
+        position: Some(position),
+    })
+}
+
 fn get_identifier_name(expr: &Expr) -> Result<String> {
     match expr {
         Expr::IdentifierExpr(name, position) => Ok(name.clone()),
@@ -100,9 +158,22 @@ fn get_identifier_name(expr: &Expr) -> Result<String> {
     }
 }
 
-fn index_of(index_table: &IndexTable, name: &str, position: Option<SourceLocation>) -> Result<usize> {
+fn get_dereferenced_operand(expr: &Expr) -> Option<&Expr> {
+    match expr {
+        Expr::PrimitiveOperationExpr(operation, _) => match &**operation {
+            PrimitiveOperation::UnaryOperation { operator: UnaryOperator::Dereference, operand } =>
+                Some(operand),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Variables are found by either their name, which resolves to the innermost variable bound with
+// it, or their binding.
+fn index_of(index_table: &IndexTable, name: &str, position: Option<Span>) -> Result<usize> {
     for (corresponding_name, index) in index_table {
-        if name == corresponding_name {
+        if name == corresponding_name || name == source_name(corresponding_name) {
             return Ok(index.clone())
         }
     }
@@ -112,6 +183,25 @@ fn index_of(index_table: &IndexTable, name: &str, position: Option<SourceLocatio
     })
 }
 
+fn num_of_frames_to_unwind(loop_table: &LoopTable, label: &Option<String>, position: Span) -> Result<usize> {
+    let enclosing_loop = match label {
+        Some(label) => loop_table.loops
+            .iter()
+            .find(|(loop_label, _)| loop_label.as_ref() == Some(label))
+            .ok_or_else(|| Error {
+                message: format!("Use of undeclared label \"{}\"", label),
+                position: Some(position),
+            })?,
+        None => loop_table.loops
+            .front()
+            .ok_or_else(|| Error {
+                message: String::from("Break or continue found outside of a loop"),
+                position: Some(position),
+            })?,
+    };
+    Ok(loop_table.num_of_frames - enclosing_loop.1)
+}
+
 fn accumulate_bytecode (acc: CompileResult, result: CompileResult) -> CompileResult {
     match acc {
         Ok(mut program_bytecode) => match result {
@@ -131,10 +221,10 @@ fn undo_index_table_changes(index_table: &mut IndexTable, undo_times: usize) {
     });
 }
 
-fn compile_top_level(stmt: &Stmt, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+fn compile_top_level(stmt: &Stmt, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
     match stmt {
         Stmt::FuncDeclaration { .. } => {
-            let mut bytecode = stmt.compile(drop_at, index_table)?;
+            let mut bytecode = stmt.compile(drop_at, index_table, loop_table)?;
             bytecode.push(Instruction::POP);
             Ok(bytecode)
         },
@@ -146,50 +236,62 @@ fn compile_top_level(stmt: &Stmt, drop_at: &ExpiredLifetimes, index_table: &mut
 }
 
 pub trait Compile {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult;
-    fn compile_drops(&self, position: &SourceLocation, drop_at: &ExpiredLifetimes) -> CompileResult {
-        drop_at.get(&position.line);
-        Ok(vec![])
+    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult;
+    // Drops the variables whose lifetimes the static checker found to end at the given position.
+    fn compile_drops(&self, span: &Span, drop_at: &ExpiredLifetimes, index_table: &IndexTable) -> Result<Vec<Instruction>> {
+        match drop_at.drops.get(&span.byte_range) {
+            Some(names) => names
+                .iter()
+                .map(|name| index_of(index_table, name, Some(*span)).map(Instruction::DROP))
+                .collect(),
+            None => Ok(vec![]),
+        }
     }
 }
 
 impl Compile for Stmt {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
         match self {
-            Stmt::LetStmt { name, value, position, .. } => match value {
+            Stmt::LetStmt { name, value, span, .. } => match value {
                 Some(expr) => {
-                    let name = get_identifier_name(name)?;
-                    let index = index_of(index_table, &name, Some(position.clone()))?;
+                    let binding = binding_name(&get_identifier_name(name)?, name.get_source_location());
 
-                    let mut bytecode = expr.compile(drop_at, index_table)?;
+                    let mut bytecode = expr.compile(drop_at, index_table, loop_table)?;
+                    let index = bind_pending(index_table, &binding, *span)?;
                     bytecode.push(Instruction::ASSIGN(index));
-                    bytecode.extend(self.compile_drops(position, drop_at)?);
                     bytecode.push(Instruction::LDCU);
 
                     Ok(bytecode)
                 },
-                None => Err(Error {
-                    message: format!("Unbounded declaration \"{}\" found and is presently unsupported", 
-                        get_identifier_name(name)?),
-                    position: Some(position.clone()),
-                })
+                // The slot of a variable declared without a value stays unassigned until assigned to.
+                None => {
+                    let binding = binding_name(&get_identifier_name(name)?, name.get_source_location());
+                    bind_pending(index_table, &binding, *span)?;
+
+                    Ok(vec![Instruction::LDCU])
+                },
             },
-            Stmt::FuncDeclaration { name, parameters, body, position, .. } => {
+            Stmt::FuncDeclaration { name, parameters, body, span, .. } => {
                 let num_of_params = parameters.len();
 
                 parameters
                     .iter()
-                    .map(|(expr, _)| get_identifier_name(expr))
+                    .map(|(expr, _, _, _)| Ok(binding_name(&get_identifier_name(expr)?, expr.get_source_location())))
                     .collect::<Result<Vec<String>>>()?
                     .into_iter()
                     .for_each(|name| {
                         index_table.push_front((name, index_table.len()));
                     });
 
-                let body_bytecode = body.compile(drop_at, index_table)?;
+                // Loops of the enclosing function cannot be broken out of from within this function.
+                let mut body_bytecode = body.compile(drop_at, index_table, &mut LoopTable::default())?;
+                if drop_at.temporary_drops.contains(&body.span.byte_range) {
+                    body_bytecode.push(Instruction::DROPTEMPS);
+                }
+                body_bytecode.push(Instruction::RTN);
 
                 let func_name = get_identifier_name(name)?;
-                let func_index = index_of(index_table, &func_name, Some(position.clone()))?;
+                let func_index = index_of(index_table, &func_name, Some(*span))?;
 
                 undo_index_table_changes(index_table, num_of_params);
 
@@ -199,17 +301,16 @@ impl Compile for Stmt {
                     Instruction::GOTOR(body_bytecode.len() + 1),
                 ];
                 bytecode.extend(body_bytecode);
-                bytecode.extend(self.compile_drops(position, drop_at)?);
                 bytecode.push(Instruction::LDCU);
 
                 Ok(bytecode)
             },
             Stmt::ExprStmt(expr) => match expr {
-                // Expression has position, so it will handle the drops.
-                Expr::ReturnExpr(..) => expr.compile(drop_at, index_table),
+                Expr::ReturnExpr(..) => expr.compile(drop_at, index_table, loop_table),
                 _ => {
-                    let mut bytecode = expr.compile(drop_at, index_table)?;
-                    bytecode.extend(vec![Instruction::POP, Instruction::LDCU]);
+                    let mut bytecode = compile_operand(expr, drop_at, index_table, loop_table)?;
+                    bytecode.push(Instruction::POP);
+                    bytecode.push(Instruction::LDCU);
                     Ok(bytecode)
                 }
             },
@@ -222,59 +323,164 @@ impl Compile for Stmt {
 }
 
 impl Compile for Expr {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
         match self {
-            Expr::IdentifierExpr(name, position) => {
-                let index = index_of(index_table, name, Some(position.clone()))?;
-                Ok(vec![Instruction::LD(index)])
+            // Moving a value out of a variable leaves it without one, so that it is not dropped again.
+            Expr::IdentifierExpr(name, span) => {
+                let index = index_of(index_table, name, Some(*span))?;
+                match drop_at.moves.contains(&span.byte_range) {
+                    true => Ok(vec![Instruction::MOVE(index)]),
+                    false => Ok(vec![Instruction::LD(index)]),
+                }
             }
-            Expr::LiteralExpr(value, position) => {
-                let mut bytecode = value.compile(drop_at, index_table)?;
-                bytecode.extend(self.compile_drops(position, drop_at)?);
-                Ok(bytecode)
-            },
-            Expr::BlockExpr(block, position) => {
-                let mut bytecode = block.compile(drop_at, index_table)?;
-                bytecode.extend(self.compile_drops(position, drop_at)?);
-                Ok(bytecode)
-            },
-            Expr::PrimitiveOperationExpr(op, position) => {
-                let mut bytecode = op.compile(drop_at, index_table)?;
-                bytecode.extend(self.compile_drops(position, drop_at)?);
+            Expr::LiteralExpr(value, _) => value.compile(drop_at, index_table, loop_table),
+            Expr::ErrorExpr(span) => Err(Error {
+                message: String::from("cannot compile source with syntax errors"),
+                position: Some(*span),
+            }),
+            Expr::BlockExpr(block, _) => block.compile(drop_at, index_table, loop_table),
+            Expr::PrimitiveOperationExpr(op, _) => op.compile(drop_at, index_table, loop_table),
+            Expr::AssignmentExpr { assignee, value, .. } if get_dereferenced_operand(assignee).is_some() => {
+                // The operand of the dereference evaluates to the reference being written through.
+                let reference = get_dereferenced_operand(assignee).unwrap();
+
+                let mut bytecode = value.compile(drop_at, index_table, loop_table)?;
+                bytecode.extend(reference.compile(drop_at, index_table, loop_table)?);
+                bytecode.push(Instruction::DEREFASSIGN);
+                bytecode.push(Instruction::LDCU);
+
                 Ok(bytecode)
             },
-            Expr::AssignmentExpr { assignee, value, position } => {
+            Expr::AssignmentExpr { assignee, value, span } => {
                 let assignee_name = get_identifier_name(assignee)?;
-                let index = index_of(index_table, &assignee_name, Some(position.clone()))?;
+                let index = index_of(index_table, &assignee_name, Some(*span))?;
 
-                let mut bytecode = value.compile(drop_at, index_table)?;
+                let mut bytecode = value.compile(drop_at, index_table, loop_table)?;
+                bytecode.extend(self.compile_drops(span, drop_at, index_table)?);
                 bytecode.push(Instruction::ASSIGN(index));
-                bytecode.extend(self.compile_drops(position, drop_at)?);
+                bytecode.push(Instruction::LDCU);
+
+                Ok(bytecode)
+            },
+            Expr::ApplicationExpr { is_primitive: Some(PrimitiveOperator::Unary(UnaryOperator::Drop)), arguments, span, .. } =>
+                match arguments.as_slice() {
+                    [operand] => compile_explicit_drop(operand, drop_at, index_table, loop_table),
+                    _ => Err(Error {
+                        message: format!("The primitive function Drop expects 1 argument(s) but {} were supplied",
+                            arguments.len()),
+                        position: Some(*span),
+                    }),
+                },
+            Expr::ApplicationExpr { is_primitive: Some(PrimitiveOperator::Unary(operator)), arguments, span, .. } => {
+                let (instruction, num_of_params) = match operator {
+                    UnaryOperator::StringFrom => (Instruction::STRFROM, 1),
+                    UnaryOperator::Len => (Instruction::LEN, 1),
+                    UnaryOperator::AsStr => (Instruction::ASSTR, 1),
+                    UnaryOperator::PushStr => (Instruction::PUSHSTR, 2),
+                    _ => return Err(Error {
+                        message: format!("The primitive function {:?} is presently unsupported", operator),
+                        position: Some(*span),
+                    }),
+                };
+
+                if arguments.len() != num_of_params {
+                    return Err(Error {
+                        message: format!("The primitive function {:?} expects {} argument(s) but {} were supplied",
+                            operator, num_of_params, arguments.len()),
+                        position: Some(*span),
+                    });
+                }
 
+                let mut bytecode = arguments
+                    .iter()
+                    .map(|arg| compile_operand(arg, drop_at, index_table, loop_table))
+                    .fold(Ok(vec![]), accumulate_bytecode)?;
+                bytecode.push(instruction);
                 Ok(bytecode)
             },
-            Expr::ApplicationExpr { callee, arguments, position, .. } => {
+            Expr::ApplicationExpr { is_primitive: Some(PrimitiveOperator::VariadicOperator(operator)), arguments, span, .. } =>
+                match operator {
+                    VariadicOperator::Println => compile_println(arguments, Some(*span), drop_at, index_table, loop_table),
+                },
+            Expr::ApplicationExpr { callee, arguments, span, .. } => {
                 // Closures (also known as anonymous functions in Rust) are presently not supported.
                 // For now, all callees would be identifiers (named).
                 let func_name = get_identifier_name(&callee)?;
-                let func_index = index_of(index_table, &func_name, Some(*position))?;
+                let func_index = index_of(index_table, &func_name, Some(*span))?;
 
                 let arg_bytecode = arguments
                     .iter()
-                    .map(|arg| arg.compile(drop_at, index_table))
+                    .map(|arg| arg.compile(drop_at, index_table, loop_table))
                     .fold(Ok(vec![]), accumulate_bytecode)?;
 
                 let mut bytecode = vec![Instruction::LD(func_index)];
                 bytecode.extend(arg_bytecode);
                 bytecode.push(Instruction::CALL(arguments.len()));
-                bytecode.extend(self.compile_drops(position, drop_at)?);
+                Ok(bytecode)
+            },
+            Expr::IfElseExpr { condition, consequent, alternative, .. } => {
+                let consequent_bytecode = consequent.compile(drop_at, index_table, loop_table)?;
+                let alternative_bytecode = match alternative {
+                    Some(alternative) => alternative.compile(drop_at, index_table, loop_table)?,
+                    None => vec![Instruction::LDCU],
+                };
+
+                let mut bytecode = condition.compile(drop_at, index_table, loop_table)?;
+                bytecode.push(Instruction::JOFR(consequent_bytecode.len() + 2));
+                bytecode.extend(consequent_bytecode);
+                bytecode.push(Instruction::GOTOR(alternative_bytecode.len() + 1));
+                bytecode.extend(alternative_bytecode);
+                Ok(bytecode)
+            },
+            Expr::LoopExpr { label, body, .. } => {
+                let body_bytecode = compile_loop_body(label, body, drop_at, index_table, loop_table)?;
 
+                let mut bytecode = vec![Instruction::LOOP(body_bytecode.len() + 3)];
+                bytecode.extend(body_bytecode);
+                bytecode.extend(vec![Instruction::POP, Instruction::CONTINUE(0)]);
+                Ok(bytecode)
+            },
+            Expr::WhileExpr { label, condition, body, .. } => {
+                // The condition is evaluated within the frame that LOOP pushes.
+                loop_table.num_of_frames += 1;
+                let condition_bytecode = condition.compile(drop_at, index_table, loop_table)?;
+                loop_table.num_of_frames -= 1;
+                let body_bytecode = compile_loop_body(label, body, drop_at, index_table, loop_table)?;
+
+                let mut bytecode = vec![Instruction::LOOP(condition_bytecode.len() + body_bytecode.len() + 6)];
+                bytecode.extend(condition_bytecode);
+                bytecode.push(Instruction::JOFR(body_bytecode.len() + 3));
+                bytecode.extend(body_bytecode);
+                bytecode.extend(vec![
+                    Instruction::POP,
+                    Instruction::CONTINUE(0),
+                    Instruction::LDCU,
+                    Instruction::BREAK(0),
+                ]);
+                Ok(bytecode)
+            },
+            Expr::BreakExpr { label, value, span } => {
+                let num_of_frames = num_of_frames_to_unwind(loop_table, label, *span)?;
+                let mut bytecode = match value {
+                    Some(value) => value.compile(drop_at, index_table, loop_table)?,
+                    None => vec![Instruction::LDCU],
+                };
+                bytecode.extend(self.compile_drops(span, drop_at, index_table)?);
+                bytecode.push(Instruction::BREAK(num_of_frames));
                 Ok(bytecode)
             },
-            Expr::ReturnExpr(expr_to_return, position) => {
-                let mut bytecode = expr_to_return.compile(drop_at, index_table)?;
-                bytecode.extend(self.compile_drops(position, drop_at)?);
-                bytecode.push(Instruction::RTN);
+            Expr::ContinueExpr { label, span } => {
+                let num_of_frames = num_of_frames_to_unwind(loop_table, label, *span)?;
+                let mut bytecode = self.compile_drops(span, drop_at, index_table)?;
+                bytecode.push(Instruction::CONTINUE(num_of_frames));
+                Ok(bytecode)
+            },
+            // The frames of the blocks and loops the return is within are left along with the call,
+            // once the variables of the function are dropped.
+            Expr::ReturnExpr(expr_to_return, span) => {
+                let mut bytecode = expr_to_return.compile(drop_at, index_table, loop_table)?;
+                bytecode.extend(self.compile_drops(span, drop_at, index_table)?);
+                bytecode.push(Instruction::RETURN(loop_table.num_of_frames));
                 Ok(bytecode)
             },
         }
@@ -282,16 +488,16 @@ impl Compile for Expr {
 }
 
 impl Compile for SequenceStmt {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
         match self {
-            SequenceStmt::Stmt(stmt) => stmt.compile(drop_at, index_table),
-            SequenceStmt::Block(block) => block.compile(drop_at, index_table),
+            SequenceStmt::Stmt(stmt) => stmt.compile(drop_at, index_table, loop_table),
+            SequenceStmt::Block(block) => block.compile(drop_at, index_table, loop_table),
         }
     }
 }
 
 impl Compile for Block {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
         let locals = scan_declaration_names_from_block(self)?;
         let num_of_locals = locals.len();
 
@@ -300,62 +506,73 @@ impl Compile for Block {
             .for_each(|name| {
                 index_table.push_front((name, index_table.len()));
             });
+        loop_table.num_of_frames += 1;
 
-        let block_bytecode = self.statements
+        // Functions can be called anywhere within the block that declares them, so they are
+        // assigned before any other statement runs.
+        let (func_declarations, other_stmts): (Vec<&SequenceStmt>, Vec<&SequenceStmt>) = self.statements
             .iter()
+            .partition(|seq_stmt| matches!(seq_stmt, SequenceStmt::Stmt(Stmt::FuncDeclaration { .. })));
+
+        // The value of the block is left on the stack to be returned, as is that of any other
+        // trailing statement.
+        let mut block_bytecode = func_declarations
+            .into_iter()
+            .chain(other_stmts)
             .enumerate()
             .map(|(index, seq_stmt)| 
-                match seq_stmt.compile(drop_at, index_table) {
-                    Ok(mut bytecode) => match seq_stmt {
-                        SequenceStmt::Stmt(_) => {
-                            if index < self.statements.len() - 1 {
-                                bytecode.push(Instruction::POP);
-                            }
-                            Ok(bytecode)
-                        },
-                        SequenceStmt::Block(_) => Ok(bytecode),
-                    },
-                    err@Err(_) => err,
-            })
+                match seq_stmt {
+                    SequenceStmt::Stmt(Stmt::ExprStmt(return_expr@Expr::ReturnExpr(value, _))) if !is_explicit_return(return_expr) =>
+                        value.compile(drop_at, index_table, loop_table),
+                    _ => seq_stmt.compile(drop_at, index_table, loop_table),
+                }.map(|mut bytecode| {
+                    if index < self.statements.len() - 1 {
+                        bytecode.push(Instruction::POP);
+                    }
+                    if drop_at.temporary_drops.contains(&seq_stmt.get_span().byte_range) {
+                        bytecode.push(Instruction::DROPTEMPS);
+                    }
+                    bytecode
+                }))
             .fold(Ok(vec![]), accumulate_bytecode)?;
+        if self.statements.is_empty() {
+            block_bytecode.push(Instruction::LDCU);
+        }
+        // The variables of the block, and the parameters of the function whose body it is, are
+        // dropped at its closing brace.
+        block_bytecode.extend(self.compile_drops(&self.span.end_point(), drop_at, index_table)?);
+        block_bytecode.push(Instruction::RTN);
 
+        loop_table.num_of_frames -= 1;
         undo_index_table_changes(index_table, num_of_locals);
 
-        let mut bytecode = vec![
-            Instruction::LDF(0, 2, num_of_locals),
-            Instruction::CALL(0),
-        ];
-        bytecode.extend(block_bytecode);
-
-        match bytecode.last() {
-            Some(instr) => match instr {
-                Instruction::RTN => (),
-                _ => bytecode.push(Instruction::RTN),
-            },
-            None => bytecode.extend(vec![Instruction::LDCU, Instruction::RTN]),
-        };
-
-        Ok(bytecode)
+        Ok(compile_frame(block_bytecode, num_of_locals))
     }
 }
 
 impl Compile for PrimitiveOperation {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
         match self {
             PrimitiveOperation::UnaryOperation { operator, operand } => {
                 let instruction = match operator {
                     UnaryOperator::Not => Instruction::NOT,
                     UnaryOperator::UnaryMinus => Instruction::UMINUS,
-                    UnaryOperator::ImmutableBorrow => unimplemented!(),
-                    UnaryOperator::MutableBorrow => unimplemented!(),
-                    UnaryOperator::Dereference => unimplemented!(),
-                    UnaryOperator::StringFrom => unimplemented!(),
-                    UnaryOperator::Drop => unimplemented!(),
-                    UnaryOperator::Len => unimplemented!(),
-                    UnaryOperator::AsStr => unimplemented!(),
-                    UnaryOperator::PushStr => unimplemented!(),
+                    UnaryOperator::ImmutableBorrow =>
+                        return compile_borrow(false, operand, drop_at, index_table, loop_table),
+                    UnaryOperator::MutableBorrow =>
+                        return compile_borrow(true, operand, drop_at, index_table, loop_table),
+                    UnaryOperator::Dereference => Instruction::DEREF,
+                    UnaryOperator::StringFrom => Instruction::STRFROM,
+                    UnaryOperator::Drop =>
+                        return compile_explicit_drop(operand, drop_at, index_table, loop_table),
+                    UnaryOperator::Len => Instruction::LEN,
+                    UnaryOperator::AsStr => Instruction::ASSTR,
+                    UnaryOperator::PushStr => return Err(Error {
+                        message: String::from("push_str expects a string to push onto"),
+                        position: None,
+                    }),
                 };
-                let mut bytecode = operand.compile(drop_at, index_table)?;
+                let mut bytecode = compile_operand(operand, drop_at, index_table, loop_table)?;
                 bytecode.push(instruction);
                 Ok(bytecode)
             },
@@ -365,6 +582,7 @@ impl Compile for PrimitiveOperation {
                     BinaryOperator::Minus => vec![Instruction::MINUS],
                     BinaryOperator::Times => vec![Instruction::TIMES],
                     BinaryOperator::Divide => vec![Instruction::DIV],
+                    BinaryOperator::Modulo => vec![Instruction::MOD],
                     BinaryOperator::Equal => vec![Instruction::EQUAL],
                     BinaryOperator::NotEqual => vec![Instruction::EQUAL, Instruction::NOT],
                     BinaryOperator::Greater => vec![Instruction::GREATER],
@@ -374,34 +592,100 @@ impl Compile for PrimitiveOperation {
                     BinaryOperator::And => vec![Instruction::AND],
                     BinaryOperator::Or => vec![Instruction::OR],
                 };
-                let mut bytecode = first_operand.compile(drop_at, index_table)?;
-                bytecode.extend(second_operand.compile(drop_at, index_table)?);
+                let mut bytecode = compile_operand(first_operand, drop_at, index_table, loop_table)?;
+                bytecode.extend(compile_operand(second_operand, drop_at, index_table, loop_table)?);
                 bytecode.extend(instructions);
                 Ok(bytecode)
             }
-            PrimitiveOperation::VariadicOperation { operator, operands } => {
-                let instruction = match operator {
-                    VariadicOperator::Println => unimplemented!(),
-                };
-                let bytecode = operands
-                    .iter()
-                    .map(|expr| expr.compile(drop_at, index_table))
-                    .fold(Ok(vec![]), accumulate_bytecode)?;
-                bytecode.push(instruction);
-                Ok(bytecode)
+            PrimitiveOperation::VariadicOperation { operator, operands } => match operator {
+                VariadicOperator::Println => compile_println(operands, None, drop_at, index_table, loop_table),
             }
         }
     }
 }
 
+// Compiles the body of a loop, which runs within the frame that LOOP pushes.
+fn compile_loop_body(label: &Option<String>, body: &Expr, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
+    loop_table.num_of_frames += 1;
+    loop_table.loops.push_front((label.clone(), loop_table.num_of_frames));
+    let body_bytecode = body.compile(drop_at, index_table, loop_table)?;
+    loop_table.loops.pop_front();
+    loop_table.num_of_frames -= 1;
+    Ok(body_bytecode)
+}
+
+fn compile_borrow(is_mutable: bool, operand: &Expr, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
+    // Reborrowing a dereferenced reference refers to the same place as the reference itself.
+    if let Some(reference) = get_dereferenced_operand(operand) {
+        return reference.compile(drop_at, index_table, loop_table);
+    }
+
+    match operand {
+        Expr::IdentifierExpr(name, span) => {
+            let index = index_of(index_table, name, Some(*span))?;
+            match is_mutable {
+                true => Ok(vec![Instruction::MUTBORROW(index)]),
+                false => Ok(vec![Instruction::BORROW(index)]),
+            }
+        },
+        // Any other value is borrowed from a temporary that lives as long as the reference.
+        _ => {
+            let mut bytecode = operand.compile(drop_at, index_table, loop_table)?;
+            bytecode.push(Instruction::TEMPBORROW);
+            Ok(bytecode)
+        },
+    }
+}
+
+// Compiles a value that is only read, which is kept to be dropped at the end of its statement if
+// it is a temporary.
+fn compile_operand(operand: &Expr, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
+    let mut bytecode = operand.compile(drop_at, index_table, loop_table)?;
+    if drop_at.temporaries.contains(&operand.get_span().byte_range) {
+        bytecode.push(Instruction::TEMP);
+    }
+    Ok(bytecode)
+}
+
+// Dropping a variable frees the value it owns, while any other value is a temporary that is
+// dropped along with the others of its statement.
+fn compile_explicit_drop(operand: &Expr, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
+    let mut bytecode = match operand {
+        Expr::IdentifierExpr(name, span) => vec![Instruction::DROP(index_of(index_table, name, Some(*span))?)],
+        _ => {
+            let mut bytecode = compile_operand(operand, drop_at, index_table, loop_table)?;
+            bytecode.push(Instruction::POP);
+            bytecode
+        },
+    };
+    bytecode.push(Instruction::LDCU);
+    Ok(bytecode)
+}
+
+fn compile_println(operands: &[Expr], position: Option<Span>, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable, loop_table: &mut LoopTable) -> CompileResult {
+    match operands.first() {
+        Some(Expr::LiteralExpr(Literal::StringLiteral(_), _)) => (),
+        _ => return Err(Error {
+            message: String::from("println expects a format string literal as its first argument"),
+            position,
+        }),
+    };
+
+    let mut bytecode = operands
+        .iter()
+        .map(|expr| compile_operand(expr, drop_at, index_table, loop_table))
+        .fold(Ok(vec![]), accumulate_bytecode)?;
+    bytecode.push(Instruction::PRINTLN(operands.len() - 1));
+    Ok(bytecode)
+}
+
 impl Compile for Literal {
-    fn compile(&self, drop_at: &ExpiredLifetimes, index_table: &mut IndexTable) -> CompileResult {
+    fn compile(&self, _drop_at: &ExpiredLifetimes, _index_table: &mut IndexTable, _loop_table: &mut LoopTable) -> CompileResult {
         match self {
             Literal::IntLiteral(value) => Ok(vec![Instruction::LDCI(*value)]),
             Literal::BoolLiteral(value) => Ok(vec![Instruction::LDCB(*value)]),
-            // Literal::StringLiteral(value) => vec![Instruction::LDCS(*value)],
+            Literal::StringLiteral(value) => Ok(vec![Instruction::LDCS(value.clone())]),
             Literal::UnitLiteral => Ok(vec![Instruction::LDCU]),
-            _ => unimplemented!()
         }
     }
 }
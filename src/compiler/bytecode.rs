@@ -0,0 +1,381 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use crate::compiler::instructions::Instruction;
+
+// Compiled programs are saved as .oxbc files: the magic number and format version, followed by
+// the number of instructions and the instructions themselves. Each instruction is its opcode,
+// followed by its operands. Integers are 8 bytes and little-endian, booleans are a byte, and
+// strings are their length in bytes followed by their UTF-8 encoding.
+const MAGIC: &[u8; 4] = b"OXBC";
+const VERSION: u8 = 1;
+
+#[derive(Debug)]
+pub struct Error {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Invalid bytecode at byte {}: {}", self.offset, self.message)
+    }
+}
+
+pub fn is_bytecode(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn encode(bytecode: &[Instruction]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    write_usize(&mut bytes, bytecode.len());
+    for instruction in bytecode {
+        bytes.push(opcode(instruction));
+        match instruction {
+            Instruction::LDCI(value) => bytes.extend(value.to_le_bytes()),
+            Instruction::LDCB(value) => bytes.push(*value as u8),
+            Instruction::LDCS(value) => {
+                write_usize(&mut bytes, value.len());
+                bytes.extend(value.as_bytes());
+            },
+            Instruction::LD(operand) | Instruction::BORROW(operand) | Instruction::MUTBORROW(operand)
+                | Instruction::DROP(operand) | Instruction::PRINTLN(operand) | Instruction::GOTOR(operand)
+                | Instruction::JOFR(operand) | Instruction::LOOP(operand) | Instruction::BREAK(operand)
+                | Instruction::CONTINUE(operand) | Instruction::ASSIGN(operand) | Instruction::CALL(operand)
+                | Instruction::MOVE(operand) | Instruction::RETURN(operand)
+                => write_usize(&mut bytes, *operand),
+            Instruction::LDF(max_stack_size, address, num_of_declarations) => {
+                write_usize(&mut bytes, *max_stack_size);
+                write_usize(&mut bytes, *address);
+                write_usize(&mut bytes, *num_of_declarations);
+            },
+            _ => (),
+        }
+    }
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<Instruction>, Error> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(Error { message: String::from("not an oxido bytecode file"), offset: 0 });
+    }
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(Error { message: format!("unsupported format version {}, expected {}", version, VERSION), offset: MAGIC.len() });
+    }
+
+    let num_of_instructions = reader.usize()?;
+    let mut bytecode = vec![];
+    let mut offsets = vec![];
+    for _ in 0..num_of_instructions {
+        offsets.push(reader.offset);
+        let instruction = match reader.byte()? {
+            0 => Instruction::START,
+            1 => Instruction::LDCI(i64::from_le_bytes(reader.take(8)?.try_into().unwrap())),
+            2 => Instruction::LDCB(reader.byte()? != 0),
+            3 => Instruction::LDCU,
+            4 => {
+                let length = reader.usize()?;
+                let start = reader.offset;
+                match String::from_utf8(reader.take(length)?.to_vec()) {
+                    Ok(value) => Instruction::LDCS(value),
+                    Err(_) => return Err(Error { message: String::from("string is not valid UTF-8"), offset: start }),
+                }
+            },
+            5 => Instruction::LD(reader.usize()?),
+            6 => Instruction::PLUS,
+            7 => Instruction::MINUS,
+            8 => Instruction::TIMES,
+            9 => Instruction::DIV,
+            10 => Instruction::MOD,
+            11 => Instruction::EQUAL,
+            12 => Instruction::GREATER,
+            13 => Instruction::GEQ,
+            14 => Instruction::LESS,
+            15 => Instruction::LEQ,
+            16 => Instruction::AND,
+            17 => Instruction::OR,
+            18 => Instruction::NOT,
+            19 => Instruction::UMINUS,
+            20 => Instruction::STRFROM,
+            21 => Instruction::LEN,
+            22 => Instruction::ASSTR,
+            23 => Instruction::PUSHSTR,
+            24 => Instruction::BORROW(reader.usize()?),
+            25 => Instruction::MUTBORROW(reader.usize()?),
+            26 => Instruction::TEMPBORROW,
+            27 => Instruction::DEREF,
+            28 => Instruction::DEREFASSIGN,
+            29 => Instruction::DROP(reader.usize()?),
+            30 => Instruction::PRINTLN(reader.usize()?),
+            31 => Instruction::POP,
+            32 => Instruction::GOTOR(reader.usize()?),
+            33 => Instruction::JOFR(reader.usize()?),
+            34 => Instruction::LOOP(reader.usize()?),
+            35 => Instruction::BREAK(reader.usize()?),
+            36 => Instruction::CONTINUE(reader.usize()?),
+            37 => Instruction::ASSIGN(reader.usize()?),
+            38 => Instruction::LDF(reader.usize()?, reader.usize()?, reader.usize()?),
+            39 => Instruction::CALL(reader.usize()?),
+            40 => Instruction::RTN,
+            41 => Instruction::DONE,
+            42 => Instruction::MOVE(reader.usize()?),
+            43 => Instruction::RETURN(reader.usize()?),
+            44 => Instruction::TEMP,
+            45 => Instruction::DROPTEMPS,
+            opcode => {
+                reader.offset -= 1;
+                return reader.error(format!("unknown opcode {}", opcode));
+            },
+        };
+        bytecode.push(instruction);
+    }
+    if reader.offset != bytes.len() {
+        return reader.error(String::from("trailing bytes after the last instruction"));
+    }
+    validate(&bytecode, &offsets)?;
+    Ok(bytecode)
+}
+
+// Jumps and functions have to lead to an instruction, and variables have to be within the frames
+// of the functions, which together have as many slots as the declarations of every function.
+fn validate(bytecode: &[Instruction], offsets: &[usize]) -> Result<(), Error> {
+    let num_of_slots = bytecode.iter()
+        .map(|instruction| match instruction {
+            Instruction::LDF(_, _, num_of_declarations) => *num_of_declarations,
+            _ => 0,
+        })
+        .try_fold(0usize, usize::checked_add);
+    for (address, instruction) in bytecode.iter().enumerate() {
+        let error = |message: String| Err(Error { message, offset: offsets[address] });
+        match instruction {
+            Instruction::GOTOR(offset) | Instruction::JOFR(offset) | Instruction::LOOP(offset)
+                | Instruction::LDF(_, offset, _) => match address.checked_add(*offset) {
+                    Some(target) if target < bytecode.len() => (),
+                    _ => return error(format!("`{}` at instruction {} leads past the last instruction", instruction, address)),
+                },
+            Instruction::LD(index) | Instruction::BORROW(index) | Instruction::MUTBORROW(index)
+                | Instruction::DROP(index) | Instruction::MOVE(index) | Instruction::ASSIGN(index)
+                if num_of_slots.is_some_and(|num_of_slots| *index >= num_of_slots) =>
+                    return error(format!("`{}` at instruction {} refers to a variable that no function declares", instruction, address)),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+// Lists the instructions with their addresses, along with the address each jump goes to.
+pub fn disassemble(bytecode: &[Instruction]) -> String {
+    let width = bytecode.len().saturating_sub(1).to_string().len();
+    let mut listing = String::new();
+    for (address, instruction) in bytecode.iter().enumerate() {
+        let target = match instruction {
+            Instruction::GOTOR(offset) | Instruction::JOFR(offset) | Instruction::LOOP(offset)
+                | Instruction::LDF(_, offset, _) => format!("  -> {}", address.saturating_add(*offset)),
+            _ => String::new(),
+        };
+        listing.push_str(&format!("{:>width$}  {}{}\n", address, instruction, target, width = width));
+    }
+    listing
+}
+
+fn opcode(instruction: &Instruction) -> u8 {
+    match instruction {
+        Instruction::START => 0,
+        Instruction::LDCI(_) => 1,
+        Instruction::LDCB(_) => 2,
+        Instruction::LDCU => 3,
+        Instruction::LDCS(_) => 4,
+        Instruction::LD(_) => 5,
+        Instruction::PLUS => 6,
+        Instruction::MINUS => 7,
+        Instruction::TIMES => 8,
+        Instruction::DIV => 9,
+        Instruction::MOD => 10,
+        Instruction::EQUAL => 11,
+        Instruction::GREATER => 12,
+        Instruction::GEQ => 13,
+        Instruction::LESS => 14,
+        Instruction::LEQ => 15,
+        Instruction::AND => 16,
+        Instruction::OR => 17,
+        Instruction::NOT => 18,
+        Instruction::UMINUS => 19,
+        Instruction::STRFROM => 20,
+        Instruction::LEN => 21,
+        Instruction::ASSTR => 22,
+        Instruction::PUSHSTR => 23,
+        Instruction::BORROW(_) => 24,
+        Instruction::MUTBORROW(_) => 25,
+        Instruction::TEMPBORROW => 26,
+        Instruction::DEREF => 27,
+        Instruction::DEREFASSIGN => 28,
+        Instruction::DROP(_) => 29,
+        Instruction::PRINTLN(_) => 30,
+        Instruction::POP => 31,
+        Instruction::GOTOR(_) => 32,
+        Instruction::JOFR(_) => 33,
+        Instruction::LOOP(_) => 34,
+        Instruction::BREAK(_) => 35,
+        Instruction::CONTINUE(_) => 36,
+        Instruction::ASSIGN(_) => 37,
+        Instruction::LDF(..) => 38,
+        Instruction::CALL(_) => 39,
+        Instruction::RTN => 40,
+        Instruction::DONE => 41,
+        Instruction::MOVE(_) => 42,
+        Instruction::RETURN(_) => 43,
+        Instruction::TEMP => 44,
+        Instruction::DROPTEMPS => 45,
+    }
+}
+
+fn write_usize(bytes: &mut Vec<u8>, value: usize) {
+    bytes.extend((value as u64).to_le_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error<T>(&self, message: String) -> Result<T, Error> {
+        Err(Error { message, offset: self.offset })
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        match self.bytes.get(self.offset..self.offset.saturating_add(length)) {
+            Some(taken) => {
+                self.offset += length;
+                Ok(taken)
+            },
+            None => self.error(String::from("unexpected end of file")),
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn usize(&mut self) -> Result<usize, Error> {
+        let value = u64::from_le_bytes(self.take(8)?.try_into().unwrap());
+        match usize::try_from(value) {
+            Ok(value) => Ok(value),
+            Err(_) => self.error(format!("operand {} is too large", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A function that loops over a string, using every kind of operand.
+    fn sample() -> Vec<Instruction> {
+        vec![
+            Instruction::START,
+            Instruction::LDF(0, 3, 2),
+            Instruction::CALL(0),
+            Instruction::GOTOR(12),
+            Instruction::LDCS(String::from("héllo")),
+            Instruction::STRFROM,
+            Instruction::ASSIGN(0),
+            Instruction::LOOP(6),
+            Instruction::LDCB(false),
+            Instruction::JOFR(2),
+            Instruction::CONTINUE(0),
+            Instruction::MOVE(0),
+            Instruction::BREAK(0),
+            Instruction::DROP(0),
+            Instruction::LDCI(-42),
+            Instruction::RTN,
+            Instruction::DONE,
+        ]
+    }
+
+    fn error_of(bytes: &[u8]) -> Error {
+        decode(bytes).expect_err("decoded invalid bytecode")
+    }
+
+    #[test]
+    fn decoding_gives_back_the_encoded_instructions() {
+        assert_eq!(decode(&encode(&sample())).unwrap(), sample());
+    }
+
+    #[test]
+    fn every_instruction_is_decoded_as_itself() {
+        // the function declares the variable the others refer to, and jumps go to themselves.
+        let bytecode = vec![
+            Instruction::LDF(0, 0, 1), Instruction::START, Instruction::LDCI(i64::MIN), Instruction::LDCB(true),
+            Instruction::LDCU, Instruction::LDCS(String::new()), Instruction::LD(0), Instruction::PLUS, Instruction::MINUS,
+            Instruction::TIMES, Instruction::DIV, Instruction::MOD, Instruction::EQUAL, Instruction::GREATER, Instruction::GEQ,
+            Instruction::LESS, Instruction::LEQ, Instruction::AND, Instruction::OR, Instruction::NOT, Instruction::UMINUS,
+            Instruction::STRFROM, Instruction::LEN, Instruction::ASSTR, Instruction::PUSHSTR, Instruction::BORROW(0),
+            Instruction::MUTBORROW(0), Instruction::TEMPBORROW, Instruction::DEREF, Instruction::DEREFASSIGN,
+            Instruction::DROP(0), Instruction::MOVE(0), Instruction::PRINTLN(3), Instruction::POP, Instruction::GOTOR(0),
+            Instruction::JOFR(0), Instruction::LOOP(0), Instruction::BREAK(1), Instruction::CONTINUE(2), Instruction::ASSIGN(0),
+            Instruction::CALL(4), Instruction::RTN, Instruction::DONE, Instruction::RETURN(1),
+            Instruction::TEMP, Instruction::DROPTEMPS,
+        ];
+        let mut opcodes: Vec<u8> = bytecode.iter().map(opcode).collect();
+        opcodes.sort();
+        assert_eq!(opcodes, (0..=45).collect::<Vec<u8>>());
+        assert_eq!(decode(&encode(&bytecode)).unwrap(), bytecode);
+    }
+
+    #[test]
+    fn truncated_file_is_rejected() {
+        let bytes = encode(&sample());
+        for length in 0..bytes.len() {
+            assert_eq!(error_of(&bytes[..length]).message, "unexpected end of file");
+        }
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        assert_eq!(error_of(b"fn main() {}").message, "not an oxido bytecode file");
+
+        let mut bytes = encode(&sample());
+        bytes[MAGIC.len()] = VERSION + 1;
+        assert_eq!(error_of(&bytes).to_string(), format!("Invalid bytecode at byte 4: unsupported format version {}, expected {}", VERSION + 1, VERSION));
+    }
+
+    #[test]
+    fn corrupt_file_is_rejected() {
+        let bytes = encode(&sample());
+        let mut unknown_opcode = bytes.clone();
+        unknown_opcode[13] = 200;
+        assert_eq!(error_of(&unknown_opcode).to_string(), "Invalid bytecode at byte 13: unknown opcode 200");
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(error_of(&trailing).message, "trailing bytes after the last instruction");
+
+        let mut invalid_string = sample();
+        invalid_string[4] = Instruction::LDCS(String::from("é"));
+        let mut invalid_string = encode(&invalid_string);
+        let position = invalid_string.iter().position(|byte| *byte == 0xc3).unwrap();
+        invalid_string[position] = 0xff;
+        assert_eq!(error_of(&invalid_string).message, "string is not valid UTF-8");
+    }
+
+    #[test]
+    fn jumps_past_the_last_instruction_are_rejected() {
+        for jump in [Instruction::GOTOR(usize::MAX), Instruction::JOFR(14), Instruction::LOOP(1 << 40), Instruction::LDF(0, 17, 0)] {
+            let mut bytecode = sample();
+            bytecode[3] = jump.clone();
+            let error = error_of(&encode(&bytecode));
+            assert_eq!(error.message, format!("`{}` at instruction 3 leads past the last instruction", jump));
+            assert_eq!(error.offset, encode(&bytecode[..3]).len());
+        }
+    }
+
+    #[test]
+    fn variables_no_function_declares_are_rejected() {
+        let mut bytecode = sample();
+        bytecode[11] = Instruction::MOVE(2);
+        assert_eq!(error_of(&encode(&bytecode)).message, "`MOVE 2` at instruction 11 refers to a variable that no function declares");
+    }
+}
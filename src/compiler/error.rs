@@ -1,16 +1,26 @@
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result;
-use crate::parser::ast::SourceLocation;
+use crate::diagnostic::Diagnostic;
+use crate::parser::ast::Span;
 
 #[derive(Debug)]
 pub struct Error {
     pub message: String,
-    pub position: Option<SourceLocation>,
+    pub position: Option<Span>,
+}
+
+impl Error {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self.position {
+            Some(span) => Diagnostic::error(self.message.clone(), span, ""),
+            None => Diagnostic::unlocated_error(self.message.clone()),
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        write!(f, "To be implemented")
+        write!(f, "{}", self.to_diagnostic())
     }
-}
\ No newline at end of file
+}
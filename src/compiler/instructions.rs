@@ -1,15 +1,21 @@
-#[derive(Debug)]
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
     START,
     LDCI(i64),
     LDCB(bool),
     LDCU,
+    LDCS(String),
     LD(usize),
     // Binary operations.
     PLUS,
     MINUS,
     TIMES,
     DIV,
+    MOD,
     EQUAL,
     GREATER,
     GEQ,
@@ -20,24 +26,63 @@ pub enum Instruction {
     // Unary operations.
     NOT,
     UMINUS,
+    // String operations.
+    STRFROM,
+    LEN,
+    ASSTR,
+    PUSHSTR,
+    // Reference operations.
+    BORROW(usize),
+    MUTBORROW(usize),
+    TEMPBORROW,
+    DEREF,
+    DEREFASSIGN,
+    // Ownership operations.
+    DROP(usize), // frees the value owned by the variable at the given index
+    MOVE(usize), // loads the value of the variable at the given index, leaving it unassigned
+    TEMP, // keeps the String on top of the stack to be freed at the end of its statement
+    DROPTEMPS, // frees the Strings kept by TEMP since the start of the statement
     // Others.
+    PRINTLN(usize), // number of arguments after the format string
     POP,
     GOTOR(usize),
+    JOFR(usize), // jumps by the given offset if the popped value is false
+    LOOP(usize), // offset to the instruction after the loop
+    BREAK(usize), // number of frames to unwind before leaving the loop
+    CONTINUE(usize), // number of frames to unwind before restarting the loop
     ASSIGN(usize),
-    LDF(usize, usize, usize), // max stack size, func body address, number of declarations (params + locals)
+    LDF(usize, usize, usize), // max stack size, func body address (relative to LDF), number of declarations (params + locals)
     CALL(usize),
     RTN,
-    DONE
+    DONE,
+    RETURN(usize), // number of frames to unwind before returning from the call
 }
 
-// TODO: Implement the following opcodes.
-// Load String.
-// ImmutableBorrow,
-// MutableBorrow,
-// Dereference,
-// StringFrom,
-// Drop,
-// Len,
-// AsStr,
-// PushStr,
-// Println,
\ No newline at end of file
+// The mnemonic followed by the operands, as listed by `oxido disasm`.
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Instruction::LDCI(value) => write!(f, "LDCI {}", value),
+            Instruction::LDCB(value) => write!(f, "LDCB {}", value),
+            Instruction::LDCS(value) => write!(f, "LDCS {:?}", value),
+            Instruction::LD(index) => write!(f, "LD {}", index),
+            Instruction::BORROW(index) => write!(f, "BORROW {}", index),
+            Instruction::MUTBORROW(index) => write!(f, "MUTBORROW {}", index),
+            Instruction::DROP(index) => write!(f, "DROP {}", index),
+            Instruction::MOVE(index) => write!(f, "MOVE {}", index),
+            Instruction::PRINTLN(num_of_args) => write!(f, "PRINTLN {}", num_of_args),
+            Instruction::GOTOR(offset) => write!(f, "GOTOR {}", offset),
+            Instruction::JOFR(offset) => write!(f, "JOFR {}", offset),
+            Instruction::LOOP(offset) => write!(f, "LOOP {}", offset),
+            Instruction::BREAK(num_of_frames) => write!(f, "BREAK {}", num_of_frames),
+            Instruction::CONTINUE(num_of_frames) => write!(f, "CONTINUE {}", num_of_frames),
+            Instruction::ASSIGN(index) => write!(f, "ASSIGN {}", index),
+            Instruction::LDF(max_stack_size, address, num_of_declarations)
+                => write!(f, "LDF {} {} {}", max_stack_size, address, num_of_declarations),
+            Instruction::CALL(num_of_args) => write!(f, "CALL {}", num_of_args),
+            Instruction::RETURN(num_of_frames) => write!(f, "RETURN {}", num_of_frames),
+            // the others have no operands, so their name is all there is.
+            instruction => write!(f, "{:?}", instruction),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+pub mod json;
+pub mod render;
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+use crate::parser::ast::Span;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Severity {
+    Error,
+    #[allow(dead_code)] // no check reports warnings yet.
+    Warning,
+}
+
+// A span of the source, along with what happens there.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+// A change to the source that fixes the error, such as `mut x` in place of the name of a variable
+// assigned to twice.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+}
+
+// An error found in a program, laid out as rustc does: the primary label points at the cause, and
+// the secondary labels at the other positions involved. Errors without a position in the source
+// have no primary label.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<String>,
+    pub message: String,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn error(message: String, span: Span, label: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            message,
+            primary: Some(Label { span, message: String::from(label) }),
+            secondary: vec![],
+            notes: vec![],
+            help: vec![],
+            suggestions: vec![],
+        }
+    }
+
+    pub fn unlocated_error(message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            code: None,
+            message,
+            primary: None,
+            secondary: vec![],
+            notes: vec![],
+            help: vec![],
+            suggestions: vec![],
+        }
+    }
+
+    pub fn with_code(mut self, code: &str) -> Diagnostic {
+        self.code = Some(String::from(code));
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, label: &str) -> Diagnostic {
+        self.secondary.push(Label { span, message: String::from(label) });
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(String::from(note));
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help.push(String::from(help));
+        self
+    }
+
+    pub fn with_suggestion(mut self, message: &str, span: Span, replacement: &str) -> Diagnostic {
+        self.suggestions.push(Suggestion { message: String::from(message), span, replacement: String::from(replacement) });
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.severity)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(primary) = &self.primary {
+            write!(f, "\n --> {}:{}", primary.span.start.line, primary.span.start.col)?;
+        }
+        for label in self.primary.iter().chain(self.secondary.iter()) {
+            if !label.message.is_empty() {
+                write!(f, "\n  | {}:{}: {}", label.span.start.line, label.span.start.col, label.message)?;
+            }
+        }
+        for note in &self.notes {
+            write!(f, "\n  = note: {}", note)?;
+        }
+        for help in &self.help {
+            write!(f, "\n  = help: {}", help)?;
+        }
+        for suggestion in &self.suggestions {
+            write!(f, "\n  = help: {}: `{}`", suggestion.message, suggestion.replacement)?;
+        }
+        Ok(())
+    }
+}
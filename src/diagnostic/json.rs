@@ -0,0 +1,102 @@
+use crate::diagnostic::{Diagnostic, Label, Suggestion};
+use crate::diagnostic::render::Renderer;
+use crate::parser::ast::Span;
+
+// Writes diagnostics as JSON objects, one per line, modelled on rustc's `--error-format=json`:
+//
+// {"$message_type":"diagnostic","message":"cannot assign twice to immutable variable `x`",
+//  "code":{"code":"E0384","explanation":null},"level":"error","spans":[{"file_name":"main.rs",
+//  "byte_start":61,"byte_end":66,"line_start":4,"line_end":4,"column_start":5,"column_end":10,
+//  "is_primary":true,"text":[...],"label":"cannot assign twice to immutable variable",
+//  "suggested_replacement":null,"suggestion_applicability":null,"expansion":null}, ...],
+//  "children":[{"message":"consider making this binding mutable","code":null,"level":"help",
+//  "spans":[{..., "suggested_replacement":"mut x", ...}],"children":[],"rendered":null}],
+//  "rendered":"error[E0384]: cannot assign twice to immutable variable `x`\n ..."}
+pub struct JsonEmitter<'a> {
+    file_name: &'a str,
+    lines: Vec<&'a str>,
+    renderer: Renderer<'a>,
+}
+
+impl<'a> JsonEmitter<'a> {
+    pub fn new(file_name: &'a str, source: &'a str) -> JsonEmitter<'a> {
+        JsonEmitter { file_name, lines: source.lines().collect(), renderer: Renderer::new(file_name, source, false) }
+    }
+
+    pub fn emit(&self, diagnostic: &Diagnostic) -> String {
+        let mut spans = vec![];
+        if let Some(primary) = &diagnostic.primary {
+            spans.push(self.label_span(primary, true));
+        }
+        spans.extend(diagnostic.secondary.iter().map(|label| self.label_span(label, false)));
+
+        let mut children = vec![];
+        children.extend(diagnostic.notes.iter().map(|note| child("note", note, vec![])));
+        children.extend(diagnostic.help.iter().map(|help| child("help", help, vec![])));
+        children.extend(diagnostic.suggestions.iter().map(|suggestion| child("help", &suggestion.message, vec![self.suggestion_span(suggestion)])));
+
+        let code = match &diagnostic.code {
+            Some(code) => format!("{{\"code\":{},\"explanation\":null}}", string(code)),
+            None => String::from("null"),
+        };
+        format!("{{\"$message_type\":\"diagnostic\",\"message\":{},\"code\":{},\"level\":{},\"spans\":[{}],\"children\":[{}],\"rendered\":{}}}",
+            string(&diagnostic.message), code, string(&diagnostic.severity.to_string()), spans.join(","), children.join(","),
+            string(&self.renderer.render(diagnostic)))
+    }
+
+    fn label_span(&self, label: &Label, is_primary: bool) -> String {
+        let message = match label.message.is_empty() {
+            true => String::from("null"),
+            false => string(&label.message),
+        };
+        self.span(&label.span, is_primary, &message, "null", "null")
+    }
+
+    // Replacements are only suggested where they are certain to fix the error.
+    fn suggestion_span(&self, suggestion: &Suggestion) -> String {
+        self.span(&suggestion.span, true, "null", &string(&suggestion.replacement), "\"MachineApplicable\"")
+    }
+
+    fn span(&self, span: &Span, is_primary: bool, label: &str, replacement: &str, applicability: &str) -> String {
+        format!("{{\"file_name\":{},\"byte_start\":{},\"byte_end\":{},\"line_start\":{},\"line_end\":{},\"column_start\":{},\"column_end\":{},\
+            \"is_primary\":{},\"text\":[{}],\"label\":{},\"suggested_replacement\":{},\"suggestion_applicability\":{},\"expansion\":null}}",
+            string(self.file_name), span.byte_range.0, span.byte_range.1, span.start.line, span.end.line, span.start.col, span.end.col,
+            is_primary, self.text(span).join(","), label, replacement, applicability)
+    }
+
+    // The lines of source the span covers, with the part of each that it covers.
+    fn text(&self, span: &Span) -> Vec<String> {
+        (span.start.line..=span.end.line)
+            .filter(|line_number| *line_number >= 1 && *line_number <= self.lines.len())
+            .map(|line_number| {
+                let text = self.lines[line_number - 1];
+                let highlight_start = if line_number == span.start.line { span.start.col } else { 1 };
+                let highlight_end = if line_number == span.end.line { span.end.col } else { text.chars().count() + 1 };
+                format!("{{\"text\":{},\"highlight_start\":{},\"highlight_end\":{}}}", string(text), highlight_start, highlight_end)
+            })
+            .collect()
+    }
+}
+
+fn child(level: &str, message: &str, spans: Vec<String>) -> String {
+    format!("{{\"message\":{},\"code\":null,\"level\":{},\"spans\":[{}],\"children\":[],\"rendered\":null}}",
+        string(message), string(level), spans.join(","))
+}
+
+// A JSON string literal.
+fn string(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if (c as u32) < 0x20 => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
@@ -0,0 +1,480 @@
+use crate::diagnostic::{Diagnostic, Label, Severity};
+use crate::parser::ast::Span;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const BOLD_RED: &str = "\x1b[1;31m";
+const BOLD_YELLOW: &str = "\x1b[1;33m";
+const BOLD_BLUE: &str = "\x1b[1;34m";
+
+// Lays diagnostics out the way rustc does, with the lines of source they point at:
+//
+// error[E0505]: cannot move out of `s` because it is borrowed
+//  --> borrow_dangle_error.rs:6:14
+//   |
+// 3 |     let r1 = &s;
+//   |              -- borrow of `s` occurs here
+// ...
+// 6 |     let s1 = s;
+//   |              ^ move out of `s` occurs here
+pub struct Renderer<'a> {
+    file_name: &'a str,
+    lines: Vec<&'a str>,
+    is_colored: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(file_name: &'a str, source: &'a str, is_colored: bool) -> Renderer<'a> {
+        Renderer { file_name, lines: source.lines().collect(), is_colored }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut output = self.paint(&diagnostic.severity.to_string(), self.severity_style(diagnostic.severity));
+        if let Some(code) = &diagnostic.code {
+            output.push_str(&self.paint(&format!("[{}]", code), self.severity_style(diagnostic.severity)));
+        }
+        output.push_str(&self.paint(&format!(": {}", diagnostic.message), BOLD));
+        output.push('\n');
+
+        let mut labels: Vec<(&Label, bool)> = vec![];
+        if let Some(primary) = &diagnostic.primary {
+            labels.push((primary, true));
+        }
+        labels.extend(diagnostic.secondary.iter().map(|label| (label, false)));
+        labels.retain(|(label, _)| label.span.start.line >= 1 && label.span.start.line <= self.lines.len());
+        let (multiline, labels): (Vec<_>, Vec<_>) = labels.into_iter()
+            .partition(|(label, _)| self.last_line_of(&label.span).is_some());
+        // the labels enclosing others are drawn left of them.
+        let mut multiline: Vec<Multiline> = multiline.into_iter()
+            .map(|(label, is_primary)| self.multiline(label, is_primary))
+            .collect();
+        multiline.sort_by_key(|label| (label.start_line, label.start_col, std::cmp::Reverse((label.end_line, label.end_col))));
+
+        // the gutter is as wide as the largest line number shown.
+        let line_numbers = shown_lines(&labels, &multiline);
+        let width = line_numbers.last().map_or(1, |line_number| line_number.to_string().len());
+        let gutter = " ".repeat(width);
+        if let Some(primary) = &diagnostic.primary {
+            output.push_str(&format!("{}{} {}:{}:{}\n", gutter, self.paint("-->", BOLD_BLUE),
+                self.file_name, primary.span.start.line, primary.span.start.col));
+        }
+        if !line_numbers.is_empty() {
+            output.push_str(&self.gutter_line(&gutter, ""));
+            output.push_str(&self.render_snippet(&line_numbers, &labels, &multiline, diagnostic.severity, width));
+        }
+
+        if !diagnostic.notes.is_empty() || !diagnostic.help.is_empty() || !diagnostic.suggestions.is_empty() {
+            if !line_numbers.is_empty() {
+                output.push_str(&self.gutter_line(&gutter, ""));
+            }
+            for note in &diagnostic.notes {
+                output.push_str(&format!("{} {} {}\n", gutter, self.paint("=", BOLD_BLUE), self.paint_prefixed("note", note)));
+            }
+            for help in &diagnostic.help {
+                output.push_str(&format!("{} {} {}\n", gutter, self.paint("=", BOLD_BLUE), self.paint_prefixed("help", help)));
+            }
+            for suggestion in &diagnostic.suggestions {
+                let help = format!("{}: `{}`", suggestion.message, suggestion.replacement);
+                output.push_str(&format!("{} {} {}\n", gutter, self.paint("=", BOLD_BLUE), self.paint_prefixed("help", &help)));
+            }
+        }
+        output
+    }
+
+    // The lines shown, in order, with a `...` in place of each stretch of lines left out. A label
+    // spanning several lines is drawn in the margin between the gutter and the source, from where
+    // it starts to where it ends.
+    fn render_snippet(&self, line_numbers: &[usize], labels: &[(&Label, bool)], multiline: &[Multiline], severity: Severity,
+        width: usize) -> String {
+        let gutter = " ".repeat(width);
+        let mut output = String::new();
+        let mut previous_line: Option<usize> = None;
+        for &line_number in line_numbers {
+            if let Some(previous) = previous_line.filter(|previous| line_number > previous + 1) {
+                let margin = self.margin(multiline, severity, |label| label.start_line <= previous && line_number <= label.end_line);
+                output.push_str(&format!("{}\n", format!("{:<width$}{}", "...", margin, width = width + 3).trim_end()));
+            }
+            // a label starting at the indentation of its first line is marked with a `/` there.
+            let margin = self.marked_margin(multiline, severity, |label| match label.start_line {
+                start_line if start_line == line_number && label.is_from_indent => Some("/"),
+                start_line if start_line < line_number && line_number <= label.end_line => Some("|"),
+                _ => None,
+            });
+            output.push_str(&self.source_line(line_number, &margin, width));
+
+            // the margin beside the annotations of the line, where every label on it is open.
+            let is_open = |label: &Multiline| label.start_line <= line_number && line_number <= label.end_line;
+            for (column, label) in multiline.iter().enumerate() {
+                if label.start_line == line_number && !label.is_from_indent {
+                    let margin = self.margin(&multiline[..column], severity, is_open);
+                    let connector = self.connector(&multiline[column..], label.start_col, " ", severity, |later| {
+                        (later.start_line < line_number || later.is_from_indent) && is_open(later)
+                    });
+                    output.push_str(&self.gutter_line(&gutter, &format!("{}{}", margin, connector)));
+                }
+            }
+            let mut line_labels: Vec<&(&Label, bool)> = labels.iter().filter(|(label, _)| label.span.start.line == line_number).collect();
+            if !line_labels.is_empty() {
+                line_labels.sort_by_key(|(label, is_primary)| (label.span.start.col, !is_primary));
+                let margin = self.margin(multiline, severity, is_open);
+                for annotation in self.annotate_line(line_number, &line_labels, severity) {
+                    output.push_str(&self.gutter_line(&gutter, &format!("{}{}", margin, annotation)));
+                }
+            }
+            // the labels nested innermost end first.
+            for (column, label) in multiline.iter().enumerate().rev() {
+                if label.end_line == line_number {
+                    let margin = self.margin(&multiline[..column], severity, is_open);
+                    let mut connector = self.connector(&multiline[column..], label.end_col, "|", severity, |later| {
+                        later.start_line <= line_number && line_number < later.end_line
+                    });
+                    if !label.label.message.is_empty() {
+                        connector.push(' ');
+                        connector.push_str(&self.paint(&label.label.message, self.label_style(label.is_primary, severity)));
+                    }
+                    output.push_str(&self.gutter_line(&gutter, &format!("{}{}", margin, connector)));
+                }
+            }
+            previous_line = Some(line_number);
+        }
+        output
+    }
+
+    // The last line of a span that continues on later lines, and None for a span on a single line.
+    // A span ending at the start of a line ends on the line before it.
+    fn last_line_of(&self, span: &Span) -> Option<usize> {
+        let last_line = match span.end.col {
+            1 => span.end.line - 1,
+            _ => span.end.line,
+        };
+        match last_line > span.start.line && last_line <= self.lines.len() {
+            true => Some(last_line),
+            false => None,
+        }
+    }
+
+    fn multiline<'l>(&self, label: &'l Label, is_primary: bool) -> Multiline<'l> {
+        let end_line = self.last_line_of(&label.span).unwrap();
+        let end_col = match end_line == label.span.end.line {
+            true => label.span.end.col - 1,
+            false => self.lines[end_line - 1].trim_end().chars().count().max(1),
+        };
+        let indent = self.lines[label.span.start.line - 1].chars().take_while(|c| c.is_whitespace()).count();
+        Multiline {
+            label,
+            is_primary,
+            start_line: label.span.start.line,
+            start_col: label.span.start.col,
+            end_line,
+            end_col,
+            is_from_indent: label.span.start.col <= indent + 1,
+        }
+    }
+
+    // A column for each label spanning several lines, with a `|` for those the given closure finds
+    // open.
+    fn margin<F>(&self, multiline: &[Multiline], severity: Severity, is_open: F) -> String
+        where F: Fn(&Multiline) -> bool {
+        self.marked_margin(multiline, severity, |label| is_open(label).then_some("|"))
+    }
+
+    fn marked_margin<F>(&self, multiline: &[Multiline], severity: Severity, mark: F) -> String
+        where F: Fn(&Multiline) -> Option<&'static str> {
+        multiline.iter()
+            .map(|label| match mark(label) {
+                Some(mark) => format!("{} ", self.paint(mark, self.label_style(label.is_primary, severity))),
+                None => String::from("  "),
+            })
+            .collect()
+    }
+
+    // Joins the column of the first of the given labels to the character at the given column of the
+    // source, crossing the columns of the others that the closure finds open.
+    fn connector<F>(&self, multiline: &[Multiline], col: usize, start: &str, severity: Severity, is_open: F) -> String
+        where F: Fn(&Multiline) -> bool {
+        let (label, later) = multiline.split_first().unwrap();
+        let style = self.label_style(label.is_primary, severity);
+        let mut connector = self.paint(start, style);
+        let mut underscores = String::from("_");
+        for later in later {
+            if is_open(later) {
+                connector.push_str(&self.paint(&underscores, style));
+                connector.push_str(&self.paint("|", self.label_style(later.is_primary, severity)));
+                underscores.clear();
+            } else {
+                underscores.push('_');
+            }
+            underscores.push('_');
+        }
+        underscores.push_str(&"_".repeat(col - 1));
+        let marker = if label.is_primary { "^" } else { "-" };
+        connector.push_str(&self.paint(&format!("{}{}", underscores, marker), style));
+        connector
+    }
+
+    // Underlines each label, and writes the message of the rightmost one after its underline. The
+    // messages of the others hang below their underlines, from right to left. A span enclosing the
+    // next label is underlined up to where that label starts.
+    fn annotate_line(&self, line_number: usize, labels: &[&(&Label, bool)], severity: Severity) -> Vec<String> {
+        let text = self.lines[line_number - 1];
+        let mut underline = String::new();
+        let mut end = 0;
+        for (index, (label, is_primary)) in labels.iter().enumerate() {
+            let start = (label.span.start.col - 1).max(end);
+            let mut marker_width = underline_width(&label.span, text).saturating_sub(start - (label.span.start.col - 1));
+            if let Some((next, _)) = labels.get(index + 1) {
+                marker_width = marker_width.min((next.span.start.col - 1).saturating_sub(start + 1));
+            }
+            let marker_width = marker_width.max(1);
+            let marker = if *is_primary { "^" } else { "-" };
+            underline.push_str(&" ".repeat(start - end));
+            underline.push_str(&self.paint(&marker.repeat(marker_width), self.label_style(*is_primary, severity)));
+            end = start + marker_width;
+        }
+
+        let (last, hanging) = labels.split_last().unwrap();
+        if !last.0.message.is_empty() {
+            underline.push(' ');
+            underline.push_str(&self.paint(&last.0.message, self.label_style(last.1, severity)));
+        }
+        let mut annotations = vec![underline];
+
+        let hanging: Vec<&&(&Label, bool)> = hanging.iter().filter(|(label, _)| !label.message.is_empty()).collect();
+        if hanging.is_empty() {
+            return annotations;
+        }
+        annotations.push(self.connectors(&hanging, severity));
+        for index in (0..hanging.len()).rev() {
+            let (label, is_primary) = hanging[index];
+            let mut annotation = self.connectors(&hanging[..index], severity);
+            let indent = (label.span.start.col - 1).saturating_sub(visible_width(&annotation, self.is_colored));
+            annotation.push_str(&" ".repeat(indent));
+            annotation.push_str(&self.paint(&label.message, self.label_style(*is_primary, severity)));
+            annotations.push(annotation);
+        }
+        annotations
+    }
+
+    // A `|` below the start of each of the labels.
+    fn connectors(&self, labels: &[&&(&Label, bool)], severity: Severity) -> String {
+        let mut connectors = String::new();
+        let mut end = 0;
+        for (label, is_primary) in labels {
+            let start = label.span.start.col - 1;
+            if start < end {
+                continue;
+            }
+            connectors.push_str(&" ".repeat(start - end));
+            connectors.push_str(&self.paint("|", self.label_style(*is_primary, severity)));
+            end = start + 1;
+        }
+        connectors
+    }
+
+    fn source_line(&self, line_number: usize, margin: &str, width: usize) -> String {
+        let number = self.paint(&format!("{:<width$} |", line_number, width = width), BOLD_BLUE);
+        let text = format!("{}{}", margin, self.lines[line_number - 1]);
+        match text.trim_end() {
+            "" => format!("{}\n", number),
+            text => format!("{} {}\n", number, text),
+        }
+    }
+
+    fn gutter_line(&self, gutter: &str, annotation: &str) -> String {
+        let bar = self.paint(&format!("{} |", gutter), BOLD_BLUE);
+        match annotation.is_empty() {
+            true => format!("{}\n", bar),
+            false => format!("{} {}\n", bar, annotation),
+        }
+    }
+
+    fn paint_prefixed(&self, prefix: &str, text: &str) -> String {
+        format!("{} {}", self.paint(&format!("{}:", prefix), BOLD), text)
+    }
+
+    fn severity_style(&self, severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => BOLD_RED,
+            Severity::Warning => BOLD_YELLOW,
+        }
+    }
+
+    fn label_style(&self, is_primary: bool, severity: Severity) -> &'static str {
+        match is_primary {
+            true => self.severity_style(severity),
+            false => BOLD_BLUE,
+        }
+    }
+
+    fn paint(&self, text: &str, style: &str) -> String {
+        match self.is_colored {
+            true => format!("{}{}{}", style, text, RESET),
+            false => String::from(text),
+        }
+    }
+}
+
+// A label whose span continues on later lines.
+struct Multiline<'l> {
+    label: &'l Label,
+    is_primary: bool,
+    start_line: usize,
+    start_col: usize,
+    end_line: usize,
+    // the column of the last character of the span.
+    end_col: usize,
+    // whether the span starts at the indentation of its first line.
+    is_from_indent: bool,
+}
+
+// The lines where labels start or end, in order. A line between two of them is shown as well, and
+// longer stretches of lines are left out.
+fn shown_lines(labels: &[(&Label, bool)], multiline: &[Multiline]) -> Vec<usize> {
+    let mut line_numbers: Vec<usize> = labels.iter().map(|(label, _)| label.span.start.line)
+        .chain(multiline.iter().flat_map(|label| [label.start_line, label.end_line]))
+        .collect();
+    line_numbers.sort();
+    line_numbers.dedup();
+
+    let mut shown: Vec<usize> = vec![];
+    for line_number in line_numbers {
+        if let Some(&previous) = shown.last().filter(|&&previous| line_number == previous + 2) {
+            shown.push(previous + 1);
+        }
+        shown.push(line_number);
+    }
+    shown
+}
+
+// The error ending the output, as rustc does.
+pub fn summary(diagnostics: &[Diagnostic]) -> Option<Diagnostic> {
+    let num_of_errors = diagnostics.iter().filter(|diagnostic| diagnostic.is_error()).count();
+    let num_of_warnings = diagnostics.len() - num_of_errors;
+    let warnings = match num_of_warnings {
+        0 => String::new(),
+        1 => String::from("; 1 warning emitted"),
+        _ => format!("; {} warnings emitted", num_of_warnings),
+    };
+    match num_of_errors {
+        0 => None,
+        1 => Some(Diagnostic::unlocated_error(format!("aborting due to previous error{}", warnings))),
+        _ => Some(Diagnostic::unlocated_error(format!("aborting due to {} previous errors{}", num_of_errors, warnings))),
+    }
+}
+
+// The width of the span on its first line. A span continuing on later lines is underlined up to
+// the end of the first.
+fn underline_width(span: &Span, text: &str) -> usize {
+    match span.end.line == span.start.line {
+        true => span.end.col.saturating_sub(span.start.col),
+        false => (text.trim_end().chars().count() + 1).saturating_sub(span.start.col),
+    }
+}
+
+// The number of characters shown, without the escape sequences of colors.
+fn visible_width(text: &str, is_colored: bool) -> usize {
+    if !is_colored {
+        return text.chars().count();
+    }
+    let mut width = 0;
+    let mut is_escaped = false;
+    for c in text.chars() {
+        match c {
+            '\x1b' => is_escaped = true,
+            'm' if is_escaped => is_escaped = false,
+            _ if is_escaped => (),
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ast::SourceLocation;
+
+    fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+        Span {
+            start: SourceLocation { line: start.0, col: start.1 },
+            end: SourceLocation { line: end.0, col: end.1 },
+            byte_range: (0, 0),
+        }
+    }
+
+    fn render(source: &str, diagnostic: &Diagnostic) -> String {
+        Renderer::new("main.rs", source, false).render(diagnostic)
+    }
+
+    #[test]
+    fn label_spanning_several_lines_is_drawn_in_the_margin() {
+        let source = "fn f(n : i64) -> i64 {\n    if n == 0 {\n        5\n    }\n    6\n}\n";
+        let diagnostic = Diagnostic::error(String::from("`if` may be missing an `else` clause"), span((2, 5), (4, 6)),
+            "expected `()`, found `i64`");
+        assert_eq!(render(source, &diagnostic), "\
+error: `if` may be missing an `else` clause
+ --> main.rs:2:5
+  |
+2 | /     if n == 0 {
+3 | |         5
+4 | |     }
+  | |_____^ expected `()`, found `i64`
+");
+    }
+
+    #[test]
+    fn label_starting_within_a_line_is_joined_to_the_margin() {
+        let source = "fn main() {\n    let x = f(1,\n        2);\n}\n";
+        let diagnostic = Diagnostic::error(String::from("mismatched types"), span((2, 13), (3, 11)), "expected `()`")
+            .with_label(span((2, 9), (2, 10)), "declared here");
+        assert_eq!(render(source, &diagnostic), "\
+error: mismatched types
+ --> main.rs:2:13
+  |
+2 |       let x = f(1,
+  |  _____________^
+  | |         - declared here
+3 | |         2);
+  | |__________^ expected `()`
+");
+    }
+
+    #[test]
+    fn gutter_is_as_wide_as_the_last_line_of_a_label() {
+        let source = "fn main() {\n\n\n\n\n\n\n    if true {\n        5\n    }\n}\n";
+        let diagnostic = Diagnostic::error(String::from("mismatched types"), span((8, 5), (10, 6)), "expected `()`")
+            .with_note("the note");
+        assert_eq!(render(source, &diagnostic), "\
+error: mismatched types
+  --> main.rs:8:5
+   |
+8  | /     if true {
+9  | |         5
+10 | |     }
+   | |_____^ expected `()`
+   |
+   = note: the note
+");
+    }
+
+    #[test]
+    fn lines_between_labels_are_left_out_beyond_one() {
+        let source = "fn main() {\n    let s = 1;\n\n\n\n\n    s;\n    s;\n    s;\n    s;\n}\n";
+        let diagnostic = Diagnostic::error(String::from("the error"), span((10, 5), (10, 6)), "used")
+            .with_label(span((2, 9), (2, 10)), "declared")
+            .with_label(span((8, 5), (8, 6)), "used before");
+        assert_eq!(render(source, &diagnostic), "\
+error: the error
+  --> main.rs:10:5
+   |
+2  |     let s = 1;
+   |         - declared
+...
+8  |     s;
+   |     - used before
+9  |     s;
+10 |     s;
+   |     ^ used
+");
+    }
+}
@@ -0,0 +1,60 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Literal(String),
+    Display,
+    Debug,
+}
+
+// Splits a format string into its literal segments and its "{}" and "{:?}" placeholders,
+// treating "{{" and "}}" as escaped braces.
+pub fn parse(format: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = vec![];
+    let mut literal = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            },
+            '{' => {
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => return Err(format!("Invalid format string \"{}\": expected `}}` but string was terminated", format)),
+                    }
+                }
+
+                let placeholder = match spec.as_str() {
+                    "" => Segment::Display,
+                    ":?" => Segment::Debug,
+                    _ => return Err(format!("Invalid format string \"{}\": unsupported placeholder \"{{{}}}\"", format, spec)),
+                };
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(placeholder);
+            },
+            '}' => return Err(format!("Invalid format string \"{}\": unmatched `}}` found", format)),
+            _ => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+pub fn count_placeholders(segments: &[Segment]) -> usize {
+    segments.iter()
+        .filter(|segment| !matches!(segment, Segment::Literal(_)))
+        .count()
+}
@@ -1,22 +1,288 @@
 mod parser;
 mod static_checker;
 mod compiler;
+mod vm;
+mod format_string;
+mod diagnostic;
 
 use std::env;
 use std::fs;
+use std::io::{IsTerminal, Read, Write};
+use std::path::Path;
 use std::process;
-use std::collections::HashMap;
+use compiler::bytecode;
+use compiler::instructions::Instruction;
+use diagnostic::Diagnostic;
+use diagnostic::json::JsonEmitter;
+use diagnostic::render::{Renderer, summary};
+use parser::ast::Stmt;
+
+const USAGE: &str = "\
+Usage: oxido <command> [options] <file>
+
+Commands:
+    parse     print the syntax tree of a program
+    check     check a program for errors, without compiling it
+    build     compile a program to bytecode, written to `-o <file>` or next to the program
+    run       compile and run a program, or run the bytecode built from one
+    disasm    list the instructions of a program, or of the bytecode built from one
+
+The file is read from standard input if it is `-`. A file given without a command is run.
+
+Options:
+    -o <file>               where `build` writes the bytecode, or `-` for standard output
+    --trace-drops           report each value dropped while running
+    --color=<when>          color diagnostics: auto, always or never
+    --error-format=<fmt>    report diagnostics as human-readable text or as json
+    -h, --help              print this message";
+
+// Compile errors, including errors in the arguments, exit with 1, and runtime errors with 101
+// as a panic in Rust does.
+const COMPILE_ERROR: i32 = 1;
+const RUNTIME_ERROR: i32 = 101;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Command {
+    Parse,
+    Check,
+    Build,
+    Run,
+    Disasm,
+}
+
+struct Options {
+    command: Command,
+    path: String,
+    output: Option<String>,
+    trace_drops: bool,
+    is_colored: bool,
+    is_json: bool,
+}
+
+// How errors are reported: rendered for people to read, or as JSON for tools.
+enum Emitter<'a> {
+    Human(Renderer<'a>),
+    Json(JsonEmitter<'a>),
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        println!("No file directory supplied.");
-        process::exit(1);
-    }
-
-    let source = fs::read_to_string(&args[1]).expect("Unable to read file");
-    let ast = parser::parse(&source).expect("Failed to parse given program");
-    static_checker::check(&ast);
-    let bytecode = compiler::compile(&ast, &HashMap::new());
-    println!("{:#?}", bytecode);
-}
\ No newline at end of file
+    let options = parse_arguments(env::args().skip(1).collect());
+    let file_name = match options.path.as_str() {
+        "-" => "<stdin>",
+        path => path,
+    };
+    let input = read_input(&options.path);
+
+    // bytecode built earlier is told apart from source by its magic number.
+    if bytecode::is_bytecode(&input) && matches!(options.command, Command::Run | Command::Disasm) {
+        let bytecode = match bytecode::decode(&input) {
+            Ok(bytecode) => bytecode,
+            Err(error) => fail(&format!("`{}`: {}", file_name, error)),
+        };
+        return execute(&options, &bytecode);
+    }
+
+    let source = match String::from_utf8(input) {
+        Ok(source) => source,
+        Err(_) => fail(&format!("`{}` is neither source nor bytecode, as it is not valid UTF-8", file_name)),
+    };
+    let emitter = match options.is_json {
+        true => Emitter::Json(JsonEmitter::new(file_name, &source)),
+        false => Emitter::Human(Renderer::new(file_name, &source, options.is_colored)),
+    };
+    match options.command {
+        Command::Parse => println!("{:#?}", parse(&source, &emitter)),
+        Command::Check => {
+            check(&source, &emitter);
+        },
+        Command::Build => {
+            let bytecode = compile(&source, &emitter);
+            write_output(&output_path(&options), &bytecode::encode(&bytecode));
+        },
+        Command::Run | Command::Disasm => execute(&options, &compile(&source, &emitter)),
+    }
+}
+
+fn parse_arguments(args: Vec<String>) -> Options {
+    let mut command = None;
+    let mut path = None;
+    let mut output = None;
+    let mut trace_drops = false;
+    let mut color = None;
+    let mut error_format = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            },
+            "-o" => match args.next() {
+                Some(file) => output = Some(file),
+                None => usage_error("`-o` requires a file to write to"),
+            },
+            "--trace-drops" => trace_drops = true,
+            _ if arg.starts_with("--color=") => color = Some(arg["--color=".len()..].to_string()),
+            _ if arg.starts_with("--error-format=") => error_format = Some(arg["--error-format=".len()..].to_string()),
+            _ if arg.starts_with('-') && arg != "-" => usage_error(&format!("unknown option `{}`", arg)),
+            _ if command.is_none() && path.is_none() && command_of(&arg).is_some() => command = command_of(&arg),
+            _ if path.is_none() => path = Some(arg),
+            // the first argument was taken for the file, but is a misspelled command.
+            _ if command.is_none() => usage_error(&format!("unknown command `{}`", path.unwrap())),
+            _ => usage_error(&format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    let is_colored = match color.as_deref() {
+        Some("always") => true,
+        Some("never") => false,
+        Some("auto") | None => std::io::stderr().is_terminal(),
+        Some(choice) => usage_error(&format!("invalid --color choice \"{}\": expected always, never or auto", choice)),
+    };
+    let is_json = match error_format.as_deref() {
+        Some("human") | None => false,
+        Some("json") => true,
+        Some(choice) => usage_error(&format!("invalid --error-format choice \"{}\": expected human or json", choice)),
+    };
+    let path = match path {
+        Some(path) => path,
+        None => usage_error("no file supplied"),
+    };
+    let command = command.unwrap_or(Command::Run);
+    if output.is_some() && command != Command::Build {
+        usage_error("`-o` is only used by `build`");
+    }
+    Options {
+        command,
+        path,
+        output,
+        trace_drops,
+        is_colored,
+        is_json,
+    }
+}
+
+fn command_of(arg: &str) -> Option<Command> {
+    match arg {
+        "parse" => Some(Command::Parse),
+        "check" => Some(Command::Check),
+        "build" => Some(Command::Build),
+        "run" => Some(Command::Run),
+        "disasm" => Some(Command::Disasm),
+        _ => None,
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    process::exit(COMPILE_ERROR);
+}
+
+// Stops with an error that is not about the program itself, such as a file that cannot be read.
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(COMPILE_ERROR);
+}
+
+fn read_input(path: &str) -> Vec<u8> {
+    let mut input = vec![];
+    let result = match path {
+        "-" => std::io::stdin().read_to_end(&mut input).map(|_| ()),
+        path => fs::read(path).map(|bytes| input = bytes),
+    };
+    if let Err(error) = result {
+        fail(&format!("couldn't read `{}`: {}", path, error));
+    }
+    input
+}
+
+// The bytecode of `program.rs` is written to `program.oxbc`, unless given another file.
+fn output_path(options: &Options) -> String {
+    match (&options.output, options.path.as_str()) {
+        (Some(output), _) => output.clone(),
+        (None, "-") => String::from("out.oxbc"),
+        (None, path) => Path::new(path).with_extension("oxbc").to_string_lossy().into_owned(),
+    }
+}
+
+fn write_output(path: &str, bytes: &[u8]) {
+    let result = match path {
+        "-" => std::io::stdout().write_all(bytes),
+        path => fs::write(path, bytes),
+    };
+    if let Err(error) = result {
+        fail(&format!("couldn't write `{}`: {}", path, error));
+    }
+}
+
+fn execute(options: &Options, bytecode: &[Instruction]) {
+    if options.command == Command::Disasm {
+        print!("{}", bytecode::disassemble(bytecode));
+        return;
+    }
+    if let Err(error) = vm::run(bytecode, options.trace_drops) {
+        eprintln!("{}", error);
+        process::exit(RUNTIME_ERROR);
+    }
+}
+
+// parsing carries on past syntax errors, so that all of them are reported at once.
+fn parse(source: &str, emitter: &Emitter) -> Vec<Stmt> {
+    match parser::parse_recovering(source) {
+        (Some(ast), diagnostics) if diagnostics.is_empty() => ast,
+        (_, diagnostics) => report(emitter, &diagnostics),
+    }
+}
+
+// The tree is checked even if it has syntax errors, so that the errors in the rest of the
+// program are reported along with them.
+fn check(source: &str, emitter: &Emitter) -> (Vec<Stmt>, static_checker::ExpiredLifetimes) {
+    let (ast, mut diagnostics) = parser::parse_recovering(source);
+    let ast = match ast {
+        Some(ast) => ast,
+        None => report(emitter, &diagnostics),
+    };
+    match static_checker::check(&ast) {
+        Ok(drop_at) if diagnostics.is_empty() => (ast, drop_at),
+        Ok(_) => report(emitter, &diagnostics),
+        Err(checker_diagnostics) => {
+            // names declared in source that failed to parse are unknown to the checker, which is
+            // not worth reporting on top of the syntax error.
+            let has_syntax_errors = !diagnostics.is_empty();
+            diagnostics.extend(checker_diagnostics.into_iter()
+                .filter(|diagnostic| !has_syntax_errors || diagnostic.code.as_deref() != Some("E0425")));
+            report(emitter, &diagnostics)
+        },
+    }
+}
+
+fn compile(source: &str, emitter: &Emitter) -> Vec<Instruction> {
+    let (ast, drop_at) = check(source, emitter);
+    match compiler::compile(&ast, &drop_at) {
+        Ok(bytecode) => bytecode,
+        Err(error) => report(emitter, &[error.to_diagnostic()]),
+    }
+}
+
+fn report(emitter: &Emitter, diagnostics: &[Diagnostic]) -> ! {
+    match emitter {
+        Emitter::Human(renderer) => {
+            for diagnostic in diagnostics {
+                eprintln!("{}", renderer.render(diagnostic));
+            }
+            if let Some(summary) = summary(diagnostics) {
+                eprint!("{}", renderer.render(&summary));
+            }
+        },
+        Emitter::Json(json_emitter) => {
+            for diagnostic in diagnostics {
+                eprintln!("{}", json_emitter.emit(diagnostic));
+            }
+            if let Some(summary) = summary(diagnostics) {
+                eprintln!("{}", json_emitter.emit(&summary));
+            }
+        },
+    }
+    process::exit(COMPILE_ERROR);
+}
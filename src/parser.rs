@@ -1,7 +1,10 @@
 #[allow(dead_code)]
 pub mod ast;
+mod recovery;
 
 use pest_consume::{match_nodes, Error, Parser};
+use pest::error::{ErrorVariant, InputLocation, LineColLocation};
+use crate::diagnostic::Diagnostic;
 use ast::{
     AST,
     Expr,
@@ -11,7 +14,9 @@ use ast::{
     UnaryOperator,
     BinaryOperator, 
     SourceLocation,
+    Span,
     LifetimeParameter,
+    LoopLabel,
     FuncParameter,
     Stmt,
     Block,
@@ -29,6 +34,56 @@ struct OxidoParser;
 type Result<T> = std::result::Result<T, Error<Rule>>;
 type Node<'i> = pest_consume::Node<'i, Rule, ()>;
 
+// The span of the text matched by a rule. Rules followed by an optional repetition match the
+// whitespace before it even when it is absent, so trailing whitespace is left out.
+fn span_of(input: &Node) -> Span {
+    let span = input.as_span();
+    let text = span.as_str().trim_end();
+    let (line, col) = span.start_pos().line_col();
+    let end = match text.rfind('\n') {
+        Some(last_newline) => SourceLocation {
+            line: line + text.matches('\n').count(),
+            col: text[last_newline + 1..].chars().count() + 1,
+        },
+        None => SourceLocation { line, col: col + text.chars().count() },
+    };
+    Span {
+        start: SourceLocation { line, col },
+        end,
+        byte_range: (span.start(), span.start() + text.len()),
+    }
+}
+
+// The contents of a string literal with its escapes replaced by the characters they stand for. The
+// grammar only lets valid escapes through, apart from `\u{..}` escapes that are not characters.
+fn unescape(text: &str) -> std::result::Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        let escaped = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('u') => {
+                let digits: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => return Err(format!("invalid unicode character escape `\\u{{{}}}`", digits)),
+                }
+            },
+            Some(c) => c,
+            None => return Err(String::from("unterminated escape")),
+        };
+        unescaped.push(escaped);
+    }
+    Ok(unescaped)
+}
+
 #[pest_consume::parser]
 impl OxidoParser {
     fn EOI(input: Node) -> Result<()> {
@@ -43,11 +98,11 @@ impl OxidoParser {
         Ok(match_nodes!(input.into_children();
             [static_declaration(stmt)] => stmt,
             [function_declaration(stmt)] => stmt,
+            [error_node(expr)] => Stmt::ExprStmt(expr),
         ))
     }
     fn declaration(input: Node) -> Result<Stmt> {
-        let (line, col) = input.as_span().start_pos().line_col();
-        let position = SourceLocation { line, col };
+        let span = span_of(&input);
 
         // Could probably be more concisely expressed by iterating through the input's children instead.
         let (name, is_mutable, annotation, value) = 
@@ -75,12 +130,11 @@ impl OxidoParser {
             is_mutable,
             annotation,
             value,
-            position, 
+            span,
         })
     }
     fn static_declaration(input: Node) -> Result<Stmt> {
-        let (line, col) = input.as_span().start_pos().line_col();
-        let position = SourceLocation { line, col };
+        let span = span_of(&input);
 
         Ok(match_nodes!(input.children();
             [identifier(identifier), datatype(annotation), expr(value)] =>
@@ -89,7 +143,7 @@ impl OxidoParser {
                     is_mutable: false,
                     annotation,
                     value,
-                    position,
+                    span,
                 },
             [mutable_specifier(_m), identifier(identifier), datatype(annotation), expr(value)] =>
                 Stmt::StaticStmt {
@@ -97,7 +151,7 @@ impl OxidoParser {
                     is_mutable: true,
                     annotation,
                     value,
-                    position,
+                    span,
                 },
         ))
     }
@@ -162,10 +216,10 @@ impl OxidoParser {
                     stmts
                 },
                 expr_to_return@_ => {
-                    let return_expr_position = expr_to_return.get_source_location();
+                    let return_expr_span = expr_to_return.get_span();
                     let return_expr = Expr::ReturnExpr(
                         Box::from(expr_to_return),
-                        return_expr_position,
+                        return_expr_span,
                     );
                     stmts.push(SequenceStmt::Stmt(
                         Stmt::ExprStmt(return_expr),
@@ -176,13 +230,14 @@ impl OxidoParser {
             None => stmts,
         };
 
-        let (line, col) = input.as_span().start_pos().line_col();
+        let span = span_of(&input);
         Ok(match_nodes!(input.into_children();
             [sequence(stmts), expr(mut last_expr)..] => Expr::BlockExpr(
                 Box::from(Block {
                     statements: process_stmts(stmts, last_expr.next()),
+                    span,
                 }),
-                SourceLocation { line, col },
+                span,
             )
         ))
     }
@@ -213,11 +268,20 @@ impl OxidoParser {
             [static_declaration(stmt)] => stmt,
             [function_declaration(stmt)] => stmt,
             [expr_stmt(stmt)] => stmt,
+            [error_node(expr)] => Stmt::ExprStmt(expr),
         ))
     }
     fn expr_stmt(input: Node) -> Result<Stmt> {
         Ok(match_nodes!(input.children();
             [expr(expr)] => Stmt::ExprStmt(expr),
+            [block_like_expr(expr)] => Stmt::ExprStmt(expr),
+        ))
+    }
+    fn block_like_expr(input: Node) -> Result<Expr> {
+        Ok(match_nodes!(input.into_children();
+            [if_expr(expr)] => expr,
+            [loop_expr(expr)] => expr,
+            [while_expr(expr)] => expr,
         ))
     }
     fn expr(input: Node) -> Result<Expr> {
@@ -232,9 +296,15 @@ impl OxidoParser {
             [boolean_literal(expr)] => expr,
             [unit_literal(expr)] => expr,
             [grouped_expr(expr)] => expr,
+            [if_expr(expr)] => expr,
+            [loop_expr(expr)] => expr,
+            [while_expr(expr)] => expr,
             [block(expr)] => expr,
             [return_val(expr)] => expr,
+            [break_expr(expr)] => expr,
+            [continue_expr(expr)] => expr,
             [identifier(expr)] => expr,
+            [error_node(expr)] => expr,
         ))
     }
     fn grouped_expr(input: Node) -> Result<Expr> {
@@ -243,8 +313,7 @@ impl OxidoParser {
         ))
     }
     fn assignment(input: Node) -> Result<Expr> {
-        let (line, col) = input.as_span().start_pos().line_col();
-        let position = SourceLocation { line, col };
+        let span = span_of(&input);
         
         let is_valid_assignee = |assignee: &Expr| match assignee {
             Expr::IdentifierExpr(_, _) => true,
@@ -258,34 +327,66 @@ impl OxidoParser {
             _ => false,
         };
 
-        let create_assignment_expr = |input: Node, assignee, value, position| 
+        // Compound assignments such as "x += 1" are desugared into "x = x + 1".
+        let create_assigned_value = |operator: Option<BinaryOperator>, assignee: &Expr, value: Expr| {
+            let span = assignee.get_span().to(value.get_span());
+            match operator {
+                Some(operator) => Expr::PrimitiveOperationExpr(
+                    Box::from(PrimitiveOperation::BinaryOperation {
+                        operator,
+                        first_operand: assignee.clone(),
+                        second_operand: value,
+                    }),
+                    span,
+                ),
+                None => value,
+            }
+        };
+
+        let create_assignment_expr = |input: Node, assignee, operator, value, span| 
             match is_valid_assignee(&assignee) {
                 true => Ok(Expr::AssignmentExpr {
+                    value: Box::from(create_assigned_value(operator, &assignee, value)),
                     assignee: Box::from(assignee),
-                    value: Box::from(value),
-                    position,
+                    span,
                 }),
                 false => Err(input.error("Expected assignee to be an identifier or a dereferenced expression")),
             };
 
         match_nodes!(input.children();
-            [identifier(identifier), assignment(value)] => 
-                create_assignment_expr(input, identifier, value, position),
-            [unary(operation), assignment(value)] => 
-                create_assignment_expr(input, operation, value, position),
+            [identifier(identifier), assignment_operator(operator), assignment(value)] => 
+                create_assignment_expr(input, identifier, operator, value, span),
+            [unary(operation), assignment_operator(operator), assignment(value)] => 
+                create_assignment_expr(input, operation, operator, value, span),
             [disjunction(expr)] => Ok(expr),
         )
     }
+    fn assignment_operator(input: Node) -> Result<Option<BinaryOperator>> {
+        match input.as_str() {
+            "=" => Ok(None),
+            "+=" => Ok(Some(BinaryOperator::Plus)),
+            "-=" => Ok(Some(BinaryOperator::Minus)),
+            "*=" => Ok(Some(BinaryOperator::Times)),
+            "/=" => Ok(Some(BinaryOperator::Divide)),
+            "%=" => Ok(Some(BinaryOperator::Modulo)),
+            unsupported_op => {
+                let msg = format!("The \"{}\" operator is unsupported", unsupported_op);
+                Err(input.error(msg))
+            }
+        }
+    }
     fn disjunction(input: Node) -> Result<Expr> {
-        let create_binary_expr = |operator, first_operand, second_operand, src_location| 
+        let create_binary_expr = |operator, first_operand: Expr, second_operand: Expr| {
+            let span = first_operand.get_span().to(second_operand.get_span());
             Expr::PrimitiveOperationExpr(
                 Box::from(PrimitiveOperation::BinaryOperation {
                     operator,
                     first_operand,
                     second_operand,
                 }),
-                src_location,
-            );
+                span,
+            )
+        };
 
         match_nodes!(input.children();
             [conjunction(initial_operand), conjunction(repetitions)..] => {
@@ -295,30 +396,23 @@ impl OxidoParser {
                         let mut second_operand = expr;
 
                         if repetitions.peek().is_none() {
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 BinaryOperator::Or,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         } else {
                             for first_operand in repetitions {
-                                let src_location = first_operand.get_source_location();
                                 second_operand = create_binary_expr(
                                     BinaryOperator::Or,
                                     first_operand,
                                     second_operand,
-                                    src_location,
                                 );
                             }
-
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 BinaryOperator::Or,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         }
                     },
@@ -328,15 +422,17 @@ impl OxidoParser {
         )
     }
     fn conjunction(input: Node) -> Result<Expr> {
-        let create_binary_expr = |operator, first_operand, second_operand, src_location| 
+        let create_binary_expr = |operator, first_operand: Expr, second_operand: Expr| {
+            let span = first_operand.get_span().to(second_operand.get_span());
             Expr::PrimitiveOperationExpr(
                 Box::from(PrimitiveOperation::BinaryOperation {
                     operator,
                     first_operand,
                     second_operand,
                 }),
-                src_location,
-            );
+                span,
+            )
+        };
 
         match_nodes!(input.children();
             [equality(initial_operand), equality(repetitions)..] => {
@@ -346,30 +442,23 @@ impl OxidoParser {
                         let mut second_operand = expr;
 
                         if repetitions.peek().is_none() {
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 BinaryOperator::And,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         } else {
                             for first_operand in repetitions {
-                                let src_location = first_operand.get_source_location();
                                 second_operand = create_binary_expr(
                                     BinaryOperator::And,
                                     first_operand,
                                     second_operand,
-                                    src_location,
                                 );
                             }
-
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 BinaryOperator::And,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         }
                     },
@@ -379,15 +468,17 @@ impl OxidoParser {
         )
     }
     fn equality(input: Node) -> Result<Expr> {
-        let create_binary_expr = |operator, first_operand, second_operand, src_location| 
+        let create_binary_expr = |operator, first_operand: Expr, second_operand: Expr| {
+            let span = first_operand.get_span().to(second_operand.get_span());
             Expr::PrimitiveOperationExpr(
                 Box::from(PrimitiveOperation::BinaryOperation {
                     operator,
                     first_operand,
                     second_operand,
                 }),
-                src_location,
-            );
+                span,
+            )
+        };
 
         match_nodes!(input.children();
             [comparison(initial_operand), equality_helper(repetitions)..] => {
@@ -398,31 +489,24 @@ impl OxidoParser {
                         let mut second_operand = expr;
 
                         if repetitions.peek().is_none() {
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         } else {
                             for (op, first_operand) in repetitions {
-                                let src_location = first_operand.get_source_location();
                                 second_operand = create_binary_expr(
                                     current_op,
                                     first_operand,
                                     second_operand,
-                                    src_location,
                                 );
                                 current_op = op;
                             }
-
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         }
                     },
@@ -447,15 +531,17 @@ impl OxidoParser {
         ))
     }
     fn comparison(input: Node) -> Result<Expr> {
-        let create_binary_expr = |operator, first_operand, second_operand, src_location| 
+        let create_binary_expr = |operator, first_operand: Expr, second_operand: Expr| {
+            let span = first_operand.get_span().to(second_operand.get_span());
             Expr::PrimitiveOperationExpr(
                 Box::from(PrimitiveOperation::BinaryOperation {
                     operator,
                     first_operand,
                     second_operand,
                 }),
-                src_location,
-            );
+                span,
+            )
+        };
 
         match_nodes!(input.children();
             [term(initial_operand), comparison_helper(repetitions)..] => {
@@ -466,31 +552,24 @@ impl OxidoParser {
                         let mut second_operand = expr;
 
                         if repetitions.peek().is_none() {
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         } else {
                             for (op, first_operand) in repetitions {
-                                let src_location = first_operand.get_source_location();
                                 second_operand = create_binary_expr(
                                     current_op,
                                     first_operand,
                                     second_operand,
-                                    src_location,
                                 );
                                 current_op = op;
                             }
-
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         }
                     },
@@ -517,15 +596,17 @@ impl OxidoParser {
         ))
     }
     fn term(input: Node) -> Result<Expr> {
-        let create_binary_expr = |operator, first_operand, second_operand, src_location| 
+        let create_binary_expr = |operator, first_operand: Expr, second_operand: Expr| {
+            let span = first_operand.get_span().to(second_operand.get_span());
             Expr::PrimitiveOperationExpr(
                 Box::from(PrimitiveOperation::BinaryOperation {
                     operator,
                     first_operand,
                     second_operand,
                 }),
-                src_location,
-            );
+                span,
+            )
+        };
 
         match_nodes!(input.children();
             [factor(initial_operand), term_helper(repetitions)..] => {
@@ -536,31 +617,24 @@ impl OxidoParser {
                         let mut second_operand = expr;
 
                         if repetitions.peek().is_none() {
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         } else {
                             for (op, first_operand) in repetitions {
-                                let src_location = first_operand.get_source_location();
                                 second_operand = create_binary_expr(
                                     current_op,
                                     first_operand,
                                     second_operand,
-                                    src_location,
                                 );
                                 current_op = op;
                             }
-
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         }
                     },
@@ -585,15 +659,17 @@ impl OxidoParser {
         ))
     }
     fn factor(input: Node) -> Result<Expr> {
-        let create_binary_expr = |operator, first_operand, second_operand, src_location| 
+        let create_binary_expr = |operator, first_operand: Expr, second_operand: Expr| {
+            let span = first_operand.get_span().to(second_operand.get_span());
             Expr::PrimitiveOperationExpr(
                 Box::from(PrimitiveOperation::BinaryOperation {
                     operator,
                     first_operand,
                     second_operand,
                 }),
-                src_location,
-            );
+                span,
+            )
+        };
 
         match_nodes!(input.children();
             [unary(initial_operand), factor_helper(repetitions)..] => {
@@ -604,31 +680,24 @@ impl OxidoParser {
                         let mut second_operand = expr;
 
                         if repetitions.peek().is_none() {
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         } else {
                             for (op, first_operand) in repetitions {
-                                let src_location = first_operand.get_source_location();
                                 second_operand = create_binary_expr(
                                     current_op,
                                     first_operand,
                                     second_operand,
-                                    src_location,
                                 );
                                 current_op = op;
                             }
-
-                            let src_location = initial_operand.get_source_location();
                             Ok(create_binary_expr(
                                 current_op,
                                 initial_operand,
                                 second_operand,
-                                src_location,
                             ))
                         }
                     },
@@ -641,6 +710,7 @@ impl OxidoParser {
         match input.as_str() {
             "/" => Ok(BinaryOperator::Divide),
             "*" => Ok(BinaryOperator::Times),
+            "%" => Ok(BinaryOperator::Modulo),
             unsupported_op@_ => {
                 let msg = format!("The \"{}\" operator is unsupported", unsupported_op);
                 Err(input.error(msg))
@@ -653,19 +723,18 @@ impl OxidoParser {
         ))
     }
     fn unary(input: Node) -> Result<Expr> {
-        let create_unary_expr = |operator, operand, line, col| Expr::PrimitiveOperationExpr(
+        let span = span_of(&input);
+        let create_unary_expr = |operator, operand| Expr::PrimitiveOperationExpr(
             Box::from(PrimitiveOperation::UnaryOperation {
                 operator,
                 operand,
             }),
-            SourceLocation { line, col },
+            span,
         );
 
-        let (line, col) = input.as_span().start_pos().line_col();
-
         Ok(match_nodes!(input.into_children();
             [unary_operator(op), unary(expr)] 
-                => create_unary_expr(op, expr, line, col),
+                => create_unary_expr(op, expr),
             [function_app(expr)] => expr,
         ))
     }
@@ -683,19 +752,98 @@ impl OxidoParser {
         }
     }
     fn return_val(input: Node) -> Result<Expr> {
-        let (line, col) = input.as_span().start_pos().line_col();
+        let span = span_of(&input);
         Ok(match_nodes!(input.into_children();
             [expr(expr)] => Expr::ReturnExpr(
                 Box::from(expr),
-                SourceLocation { line, col },
+                span,
             ),
         ))
     }
+    fn if_expr(input: Node) -> Result<Expr> {
+        let span = span_of(&input);
+
+        let create_if_else_expr = |condition, consequent, alternative: Option<Expr>| Expr::IfElseExpr {
+            condition: Box::from(condition),
+            consequent: Box::from(consequent),
+            alternative: alternative.map(Box::from),
+            span,
+        };
+
+        Ok(match_nodes!(input.into_children();
+            [expr(condition), block(consequent)] =>
+                create_if_else_expr(condition, consequent, None),
+            [expr(condition), block(consequent), block(alternative)] =>
+                create_if_else_expr(condition, consequent, Some(alternative)),
+            [expr(condition), block(consequent), if_expr(alternative)] =>
+                create_if_else_expr(condition, consequent, Some(alternative)),
+        ))
+    }
+    fn loop_expr(input: Node) -> Result<Expr> {
+        let span = span_of(&input);
+
+        Ok(match_nodes!(input.into_children();
+            [block(body)] => Expr::LoopExpr {
+                label: None,
+                body: Box::from(body),
+                span,
+            },
+            [loop_label(label), block(body)] => Expr::LoopExpr {
+                label: Some(label),
+                body: Box::from(body),
+                span,
+            },
+        ))
+    }
+    fn while_expr(input: Node) -> Result<Expr> {
+        let span = span_of(&input);
+
+        let create_while_expr = |label, condition, body| Expr::WhileExpr {
+            label,
+            condition: Box::from(condition),
+            body: Box::from(body),
+            span,
+        };
+
+        Ok(match_nodes!(input.into_children();
+            [expr(condition), block(body)] =>
+                create_while_expr(None, condition, body),
+            [loop_label(label), expr(condition), block(body)] =>
+                create_while_expr(Some(label), condition, body),
+        ))
+    }
+    fn break_expr(input: Node) -> Result<Expr> {
+        let span = span_of(&input);
+
+        let create_break_expr = |label, value: Option<Expr>| Expr::BreakExpr {
+            label,
+            value: value.map(Box::from),
+            span,
+        };
+
+        Ok(match_nodes!(input.into_children();
+            [] => create_break_expr(None, None),
+            [loop_label(label)] => create_break_expr(Some(label), None),
+            [expr(value)] => create_break_expr(None, Some(value)),
+            [loop_label(label), expr(value)] => create_break_expr(Some(label), Some(value)),
+        ))
+    }
+    fn continue_expr(input: Node) -> Result<Expr> {
+        let span = span_of(&input);
+
+        Ok(match_nodes!(input.into_children();
+            [] => Expr::ContinueExpr { label: None, span },
+            [loop_label(label)] => Expr::ContinueExpr { label: Some(label), span },
+        ))
+    }
+    fn loop_label(input: Node) -> Result<LoopLabel> {
+        Ok(String::from(input.as_str()))
+    }
     fn identifier(input: Node) -> Result<Expr> {
-        let (line, col) = input.as_span().start_pos().line_col();
+        let span = span_of(&input);
         Ok(Expr::IdentifierExpr(
             String::from(input.as_str().trim()),
-            SourceLocation { line, col }
+            span
         ))
     }
     fn function_declaration(input: Node) -> Result<Stmt> {
@@ -704,8 +852,7 @@ impl OxidoParser {
             _ => Err("Body (block expression) is expected in a function declaration")
         };
 
-        let (line, col) = input.as_span().start_pos().line_col();
-        let position = SourceLocation { line, col };
+        let span = span_of(&input);
 
         let create_func_decl =
             |input: Node, name, lifetime_parameters, parameters, return_type: Option<DataType>, block_expr| {
@@ -716,7 +863,7 @@ impl OxidoParser {
                     parameters,
                     return_type: return_type.unwrap_or(DataType::Unit),
                     body: block,
-                    position,
+                    span,
                 })
             };
 
@@ -757,9 +904,12 @@ impl OxidoParser {
             .collect()
     }
     fn function_param(input: Node) -> Result<FuncParameter> {
+        let span = span_of(&input);
         Ok(match_nodes!(input.children();
             [identifier(name), datatype(param_type)] => 
-                (name, param_type), 
+                (name, param_type, false, span), 
+            [mutable_specifier(_m), identifier(name), datatype(param_type)] => 
+                (name, param_type, true, span), 
         ))
     }
     fn function_app(input: Node) -> Result<Expr> {
@@ -786,14 +936,14 @@ impl OxidoParser {
             _ => None,
         };
 
-        let (line, col) = input.as_span().start_pos().line_col();
+        let span = span_of(&input);
         Ok(match_nodes!(input.into_children();
             [primary(expr)] => expr,
             [primary(callee), function_arg_list(arguments)] => Expr::ApplicationExpr {
                 is_primitive: get_prefixed_primitive_operator(&callee),
                 callee: Box::from(callee),
                 arguments,
-                position: SourceLocation { line, col },
+                span,
             },
         ))
     }
@@ -807,10 +957,10 @@ impl OxidoParser {
             .trim()
             .parse::<bool>()
             .map(|b| -> Expr {
-                let (line, col) = input.as_span().start_pos().line_col();
+                let span = span_of(&input);
                 Expr::LiteralExpr(
                     Literal::BoolLiteral(b),
-                    SourceLocation { line, col }
+                    span
                 )
             })
             .map_err(|e| input.error(e))
@@ -820,30 +970,33 @@ impl OxidoParser {
             .trim()
             .parse::<i64>()
             .map(|i| -> Expr {
-                let (line, col) = input.as_span().start_pos().line_col();
+                let span = span_of(&input);
                 Expr::LiteralExpr(
                     Literal::IntLiteral(i),
-                    SourceLocation { line, col }
+                    span
                 )
             })
             .map_err(|e| input.error(e))
     }
     fn string_literal(input: Node) -> Result<Expr> {
-        let (line, col) = input.as_span().start_pos().line_col();
-        let s = input.into_children().as_pairs().as_str();
+        let span = span_of(&input);
+        let s = unescape(input.children().as_pairs().as_str()).map_err(|e| input.error(e))?;
         let str_expr = Expr::LiteralExpr(
-            Literal::StringLiteral(String::from(s)),
-            SourceLocation { line, col }
+            Literal::StringLiteral(s),
+            span
         );
         Ok(str_expr)
     }
     fn unit_literal(input: Node) -> Result<Expr> {
-        let (line, col) = input.as_span().start_pos().line_col();
+        let span = span_of(&input);
         Ok(Expr::LiteralExpr(
             Literal::UnitLiteral,
-            SourceLocation { line, col },
+            span,
         ))
     } 
+    fn error_node(input: Node) -> Result<Expr> {
+        Ok(Expr::ErrorExpr(span_of(&input)))
+    }
 }
 
 pub fn parse(program: &str) -> Result<Vec<Stmt>> {
@@ -851,3 +1004,195 @@ pub fn parse(program: &str) -> Result<Vec<Stmt>> {
     OxidoParser::program(inputs.single()?)
 }
 
+// Parses the program, carrying on past syntax errors to find the others as well. Each statement
+// with an error is blanked out before parsing again, and is left in the tree as an error node for
+// the static checker to skip. The tree is missing if the errors could not be recovered from.
+pub fn parse_recovering(program: &str) -> (Option<Vec<Stmt>>, Vec<Diagnostic>) {
+    let mut recovery = recovery::Recovery::new(program);
+    let mut diagnostics = vec![];
+    loop {
+        if let Some(offset) = recovery.stray_filler() {
+            let c = recovery.text[offset..].chars().next().unwrap();
+            diagnostics.push(Diagnostic::error(format!("unknown start of token: {}", c.escape_unicode()),
+                span_between(&recovery.text, offset, offset + c.len_utf8()), ""));
+            if !recovery.resynchronize(offset) {
+                return (None, diagnostics);
+            }
+            continue;
+        }
+
+        let error = match parse(&recovery.text) {
+            Ok(ast) => return (Some(ast), diagnostics),
+            Err(error) => error,
+        };
+        let is_recovered = match missing_semicolon(&error, &recovery.text) {
+            Some((end, found)) => {
+                diagnostics.push(Diagnostic::error(format!("expected `;`, found `{}`", found),
+                    span_between(&recovery.text, end, end), "expected `;`")
+                    .with_label(span_between(&recovery.text, error_offset(&error), error_offset(&error) + found.len()), "unexpected token"));
+                recovery.end_statement(end)
+            },
+            None => {
+                diagnostics.push(diagnostic_of(&error, &recovery.text));
+                recovery.resynchronize(error_offset(&error))
+            },
+        };
+        if !is_recovered {
+            return (None, diagnostics);
+        }
+    }
+}
+
+fn error_offset(error: &Error<Rule>) -> usize {
+    match error.location {
+        InputLocation::Pos(offset) => offset,
+        InputLocation::Span((start, _)) => start,
+    }
+}
+
+// A statement is missing its semicolon if its expression could end where parsing stopped, and what
+// follows starts another statement: a declaration, or a name on a later line. Gives where the
+// statement ends, and the token found instead.
+fn missing_semicolon<'a>(error: &Error<Rule>, program: &'a str) -> Option<(usize, &'a str)> {
+    let could_end = match &error.variant {
+        ErrorVariant::ParsingError { positives, .. } => positives.iter().any(|rule| describe_rule(rule) == "an operator"),
+        ErrorVariant::CustomError { .. } => false,
+    };
+    let offset = error_offset(error);
+    let found = token_at(program, offset)?;
+    let end = program[..offset].trim_end().len();
+    let is_on_later_line = program[end..offset].contains('\n') && found.starts_with(|c: char| c.is_alphabetic() || c == '_');
+    match could_end && end > 0 && (matches!(found, "let" | "static" | "fn") || is_on_later_line) {
+        true => Some((end, found)),
+        false => None,
+    }
+}
+
+// The span of the given byte range of the program.
+fn span_between(program: &str, start: usize, end: usize) -> Span {
+    let location = |offset| {
+        let (line, col) = pest::Position::new(program, offset).unwrap().line_col();
+        SourceLocation { line, col }
+    };
+    Span { start: location(start), end: location(end), byte_range: (start, end) }
+}
+
+// Parse errors are reported like the errors found in later stages, pointing at the token that
+// could not be parsed.
+fn diagnostic_of(error: &Error<Rule>, program: &str) -> Diagnostic {
+    let (start, end) = match error.line_col {
+        LineColLocation::Pos(position) => (position, position),
+        LineColLocation::Span(start, end) => (start, end),
+    };
+    let byte_range = match error.location {
+        InputLocation::Pos(offset) => (offset, offset),
+        InputLocation::Span(range) => range,
+    };
+    let found = token_at(program, byte_range.0);
+    let mut span = Span {
+        start: SourceLocation { line: start.0, col: start.1 },
+        end: SourceLocation { line: end.0, col: end.1 },
+        byte_range,
+    };
+    match (&error.location, found) {
+        (InputLocation::Pos(offset), Some(token)) => {
+            span.end.col += token.chars().count();
+            span.byte_range = (*offset, offset + token.len());
+        },
+        // the end of a program ending in a line break is pointed at on its last line.
+        (_, None) if start.0 > program.lines().count() => {
+            let last_line = program.lines().last().unwrap_or("");
+            let end_of_line = SourceLocation { line: program.lines().count().max(1), col: last_line.chars().count() + 1 };
+            span.start = end_of_line;
+            span.end = end_of_line;
+        },
+        _ => (),
+    }
+
+    let found_description = match found {
+        Some(token) => format!("`{}`", token),
+        None => String::from("end of file"),
+    };
+    let message = match &error.variant {
+        ErrorVariant::ParsingError { positives, negatives } => match (negatives.is_empty(), positives.is_empty()) {
+            (true, true) => format!("unexpected {}", found_description),
+            (true, false) => format!("expected {}, found {}", describe_rules(positives), found_description),
+            (false, _) => format!("unexpected {}", describe_rules(negatives)),
+        },
+        ErrorVariant::CustomError { message } => return Diagnostic::error(message.clone(), span, ""),
+    };
+    let label = match found {
+        Some(_) => "unexpected token",
+        None => "",
+    };
+    Diagnostic::error(message, span, label)
+}
+
+// The token starting at the offset, or None at the end of the program.
+fn token_at(program: &str, offset: usize) -> Option<&str> {
+    const OPERATORS: [&str; 13] = ["==", "!=", ">=", "<=", "&&", "||", "->", "+=", "-=", "*=", "/=", "%=", "()"];
+    let rest = &program[offset.min(program.len())..];
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_';
+    let length = match rest.chars().next() {
+        None => return None,
+        Some('"') => rest[1..].find('"').map_or(rest.len(), |end| end + 2),
+        Some(c) if is_name_char(c) => rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len()),
+        Some(c) => match OPERATORS.iter().find(|operator| rest.starts_with(*operator)) {
+            Some(operator) => operator.len(),
+            None => c.len_utf8(),
+        },
+    };
+    Some(&rest[..length])
+}
+
+// The rules expected by the parser, in the terms a programmer knows them by.
+fn describe_rules(rules: &[Rule]) -> String {
+    let mut descriptions: Vec<&str> = vec![];
+    for rule in rules {
+        let description = describe_rule(rule);
+        if !descriptions.contains(&description) {
+            descriptions.push(description);
+        }
+    }
+    match descriptions.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, [first])) => format!("{} or {}", first, last),
+        Some((last, rest)) => format!("{}, or {}", rest.join(", "), last),
+        None => String::new(),
+    }
+}
+
+fn describe_rule(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::EOI => "end of file",
+        Rule::program | Rule::top_level_declarations | Rule::function_declaration => "`fn`",
+        Rule::static_declaration => "`static`",
+        Rule::declaration => "`let`",
+        Rule::mutable_specifier => "`mut`",
+        Rule::datatype | Rule::reference_datatype | Rule::function_datatype | Rule::function_datatype_param_list => "a type",
+        Rule::block => "`{`",
+        Rule::sequence | Rule::stmt | Rule::expr_stmt => "a statement",
+        Rule::expr | Rule::assignment | Rule::disjunction | Rule::conjunction | Rule::equality | Rule::comparison
+            | Rule::term | Rule::factor | Rule::unary | Rule::function_app | Rule::function_arg_list | Rule::primary
+            | Rule::grouped_expr | Rule::block_like_expr | Rule::error_node => "an expression",
+        Rule::assignment_operator | Rule::equality_operator | Rule::equality_helper | Rule::comparison_operator | Rule::comparison_helper
+            | Rule::term_operator | Rule::term_helper | Rule::factor_operator | Rule::factor_helper => "an operator",
+        Rule::unary_operator => "a unary operator",
+        Rule::return_val => "`return`",
+        Rule::if_expr => "`if`",
+        Rule::loop_expr => "`loop`",
+        Rule::while_expr => "`while`",
+        Rule::break_expr => "`break`",
+        Rule::continue_expr => "`continue`",
+        Rule::loop_label => "a loop label",
+        Rule::identifier => "an identifier",
+        Rule::function_return_type => "`->`",
+        Rule::lifetime_param_list | Rule::lifetime_type_variable => "a lifetime",
+        Rule::function_param_list | Rule::function_param => "a parameter",
+        Rule::boolean_literal | Rule::integer_literal | Rule::string_literal | Rule::unit_literal => "a literal",
+        Rule::inner | Rule::char => "a character",
+        Rule::keyword => "a keyword",
+        Rule::error_filler => "an expression",
+        Rule::WHITESPACE | Rule::COMMENT => "whitespace",
+    }
+}
@@ -1,8 +1,12 @@
 #[allow(dead_code)]
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 
 pub trait AST {
-    fn get_source_location(&self) -> SourceLocation; 
+    fn get_span(&self) -> Span;
+
+    fn get_source_location(&self) -> SourceLocation {
+        self.get_span().start
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -11,8 +15,56 @@ pub struct SourceLocation {
     pub col: usize,
 }
 
+// The stretch of source code a node is parsed from. The end is the position just past its last
+// character, and the byte range is the offsets of both in the source.
+#[derive(Debug, Copy, Clone)]
+pub struct Span {
+    pub start: SourceLocation,
+    pub end: SourceLocation,
+    pub byte_range: (usize, usize),
+}
+
+impl Span {
+    // The span from the start of this one to the end of the other.
+    pub fn to(&self, other: Span) -> Span {
+        Span { start: self.start, end: other.end, byte_range: (self.byte_range.0, other.byte_range.1) }
+    }
+
+    // The last character of the span, such as the closing brace of a block.
+    pub fn end_point(&self) -> Span {
+        let (start, end) = self.byte_range;
+        Span {
+            start: SourceLocation { line: self.end.line, col: self.end.col.saturating_sub(1).max(1) },
+            end: self.end,
+            byte_range: (end.saturating_sub(1).max(start), end),
+        }
+    }
+}
+
+// A name can be declared again to shadow the earlier binding, so once names are resolved, each
+// variable is known by its name together with where it is declared.
+pub fn binding_name(name: &str, declared_at: SourceLocation) -> String {
+    format!("{}@{}:{}", name, declared_at.line, declared_at.col)
+}
+
+// The name a binding is declared with.
+pub fn source_name(binding: &str) -> &str {
+    match binding.split_once('@') {
+        Some((name, _)) => name,
+        None => binding,
+    }
+}
+
 pub type LifetimeParameter = String;
 
+// Elided lifetimes are named after their position among the parameters, with names that cannot
+// be written in source code.
+pub fn is_elided_lifetime(lifetime: &str) -> bool {
+    lifetime.starts_with("'_")
+}
+
+pub type LoopLabel = String;
+
 #[derive(Debug, Clone)]
 pub enum DataType {
     Int64,
@@ -23,6 +75,38 @@ pub enum DataType {
     Ref(Option<LifetimeParameter>, Box<DataType>),
     MutRef(Option<LifetimeParameter>, Box<DataType>),
     Func(Vec<LifetimeParameter>, Vec<DataType>, Box<DataType>),
+    // the type of a loop that is never left, which has no value, so it coerces to any other type.
+    Never,
+    // the type of an expression that failed to type check, which is compatible with any other so
+    // that the error is only reported once.
+    Unknown,
+}
+
+// Types are displayed the way they are written in source code, so elided lifetimes are left out.
+impl Display for DataType {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            DataType::Int64 => write!(f, "i64"),
+            DataType::Bool => write!(f, "bool"),
+            DataType::Str => write!(f, "str"),
+            DataType::String => write!(f, "String"),
+            DataType::Unit => write!(f, "()"),
+            DataType::Unknown => write!(f, "{{unknown}}"),
+            DataType::Never => write!(f, "!"),
+            DataType::Ref(Some(lifetime), datatype) if !is_elided_lifetime(lifetime) => write!(f, "&{} {}", lifetime, datatype),
+            DataType::Ref(_, datatype) => write!(f, "&{}", datatype),
+            DataType::MutRef(Some(lifetime), datatype) if !is_elided_lifetime(lifetime) => write!(f, "&{} mut {}", lifetime, datatype),
+            DataType::MutRef(_, datatype) => write!(f, "&mut {}", datatype),
+            DataType::Func(_, param_types, return_type) => {
+                let param_types: Vec<String> = param_types.iter().map(|param_type| param_type.to_string()).collect();
+                write!(f, "fn({})", param_types.join(", "))?;
+                match **return_type {
+                    DataType::Unit => Ok(()),
+                    _ => write!(f, " -> {}", return_type),
+                }
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -34,52 +118,112 @@ pub enum Literal {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // statements are larger than blocks for the spans of their parts.
 pub enum SequenceStmt {
     Stmt(Stmt),
     Block(Block),
 }
 
+impl AST for SequenceStmt {
+    fn get_span(&self) -> Span {
+        match self {
+            SequenceStmt::Stmt(stmt) => stmt.get_span(),
+            SequenceStmt::Block(block) => block.get_span(),
+        }
+    }
+}
+
 pub type Sequence = Vec<SequenceStmt>;
 
 #[derive(Debug, Clone)]
 pub struct Block {
     pub statements: Sequence,
+    pub span: Span,
+}
+
+impl AST for Block {
+    fn get_span(&self) -> Span {
+        self.span
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Expr {
-    IdentifierExpr(String, SourceLocation),
-    LiteralExpr(Literal, SourceLocation),
-    BlockExpr(Box<Block>, SourceLocation),
-    PrimitiveOperationExpr(Box<PrimitiveOperation>, SourceLocation),
+    IdentifierExpr(String, Span),
+    LiteralExpr(Literal, Span),
+    BlockExpr(Box<Block>, Span),
+    PrimitiveOperationExpr(Box<PrimitiveOperation>, Span),
     AssignmentExpr {
         assignee: Box<Expr>,
         value: Box<Expr>,
-        position: SourceLocation,
+        span: Span,
     },
     ApplicationExpr {
         is_primitive: Option<PrimitiveOperator>,
         callee: Box<Expr>,
         arguments: Vec<Expr>,
-        position: SourceLocation,
+        span: Span,
+    },
+    ReturnExpr(Box<Expr>, Span),
+    IfElseExpr {
+        condition: Box<Expr>,
+        consequent: Box<Expr>,
+        alternative: Option<Box<Expr>>,
+        span: Span,
+    },
+    LoopExpr {
+        label: Option<LoopLabel>,
+        body: Box<Expr>,
+        span: Span,
     },
-    ReturnExpr(Box<Expr>, SourceLocation),
+    WhileExpr {
+        label: Option<LoopLabel>,
+        condition: Box<Expr>,
+        body: Box<Expr>,
+        span: Span,
+    },
+    BreakExpr {
+        label: Option<LoopLabel>,
+        value: Option<Box<Expr>>,
+        span: Span,
+    },
+    ContinueExpr {
+        label: Option<LoopLabel>,
+        span: Span,
+    },
+    // source that failed to parse, in place of an expression or of a whole statement.
+    ErrorExpr(Span),
 }
 
 impl AST for Expr {
-    fn get_source_location(&self) -> SourceLocation {
+    fn get_span(&self) -> Span {
         match self {
-            Expr::IdentifierExpr(_, position) => position.clone(),
-            Expr::LiteralExpr(_, position) => position.clone(),
-            Expr::BlockExpr(_, position) => position.clone(),
-            Expr::PrimitiveOperationExpr(_, position) => position.clone(),
-            Expr::AssignmentExpr { position, .. } => position.clone(),
-            Expr::ApplicationExpr { position, .. } => position.clone(),
-            Expr::ReturnExpr(_, position) => position.clone(),
+            Expr::IdentifierExpr(_, span) => *span,
+            Expr::LiteralExpr(_, span) => *span,
+            Expr::BlockExpr(_, span) => *span,
+            Expr::PrimitiveOperationExpr(_, span) => *span,
+            Expr::AssignmentExpr { span, .. } => *span,
+            Expr::ApplicationExpr { span, .. } => *span,
+            Expr::ReturnExpr(_, span) => *span,
+            Expr::IfElseExpr { span, .. } => *span,
+            Expr::LoopExpr { span, .. } => *span,
+            Expr::WhileExpr { span, .. } => *span,
+            Expr::BreakExpr { span, .. } => *span,
+            Expr::ContinueExpr { span, .. } => *span,
+            Expr::ErrorExpr(span) => *span,
         }
     }
 }
 
+// The value of a block is kept as a return expression at its end, which spans just the value,
+// while a `return` written in source also spans its keyword.
+pub fn is_explicit_return(expr: &Expr) -> bool {
+    match expr {
+        Expr::ReturnExpr(value, span) => value.get_span().byte_range != span.byte_range,
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum PrimitiveOperation {
     UnaryOperation {
@@ -130,6 +274,7 @@ pub enum BinaryOperator {
     Minus,
     Times,
     Divide,
+    Modulo,
     Equal,
     NotEqual,
     Greater,
@@ -145,7 +290,13 @@ pub enum VariadicOperator {
     Println,
 }
 
-pub type FuncParameter = (Expr, DataType);
+pub type FuncParameter = (Expr, DataType, bool, Span); // name, type, mutability and span.
+
+impl AST for FuncParameter {
+    fn get_span(&self) -> Span {
+        self.3
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
@@ -154,14 +305,14 @@ pub enum Stmt {
         is_mutable: bool,
         annotation: Option<DataType>,
         value: Option<Expr>,
-        position: SourceLocation,
+        span: Span,
     },
     StaticStmt {
         name: Expr,
         is_mutable: bool,
         annotation: DataType,
         value: Expr,
-        position: SourceLocation,
+        span: Span,
     },
     FuncDeclaration {
         name: Expr,
@@ -169,18 +320,18 @@ pub enum Stmt {
         parameters: Vec<FuncParameter>,
         return_type: DataType,
         body: Block,
-        position: SourceLocation,
+        span: Span,
     },
     ExprStmt(Expr),
 }
 
 impl AST for Stmt {
-    fn get_source_location(&self) -> SourceLocation {
+    fn get_span(&self) -> Span {
         match self {
-            Stmt::LetStmt { position, .. } => position.clone(),
-            Stmt::StaticStmt { position, .. } => position.clone(),
-            Stmt::FuncDeclaration { position, .. } => position.clone(),
-            Stmt::ExprStmt(expr) => expr.get_source_location(),
+            Stmt::LetStmt { span, .. } => *span,
+            Stmt::StaticStmt { span, .. } => *span,
+            Stmt::FuncDeclaration { span, .. } => *span,
+            Stmt::ExprStmt(expr) => expr.get_span(),
         }
     }
 }
\ No newline at end of file
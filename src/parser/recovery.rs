@@ -0,0 +1,255 @@
+// Recovery from syntax errors. The statement around an error is blanked out, so that parsing the
+// program again gets past it and finds the next error, if any. Statements are delimited by the
+// boundaries below, which parsing resynchronizes at. The ranges blanked out are recorded, as the
+// characters standing in for them could also be found in the program itself, where they are
+// reported instead.
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Boundary {
+    Semicolon,
+    OpenBrace,
+    CloseBrace,
+    Fn,
+}
+
+// The source being parsed again, with the ranges blanked out of it so far.
+pub struct Recovery {
+    pub text: String,
+    blanked: Vec<(usize, usize)>,
+}
+
+impl Recovery {
+    pub fn new(program: &str) -> Recovery {
+        Recovery { text: String::from(program), blanked: vec![] }
+    }
+
+    // Blanks out the source around the syntax error at the given offset, or closes the blocks left
+    // open if the error is at the end of the source. Returns false if nothing can be done about it.
+    pub fn resynchronize(&mut self, offset: usize) -> bool {
+        let boundaries = boundaries_of(&self.text);
+        let (start, end) = region_of(&self.text, offset, &boundaries, &self.blanked);
+        if self.text[start..end].char_indices().any(|(position, c)| !c.is_whitespace() && !self.is_blanked(start + position)) {
+            self.blank(start, end);
+            return true;
+        }
+        // a closing brace with no block to close is blanked out on its own.
+        if boundaries.iter().any(|(position, boundary)| *position == offset && *boundary == Boundary::CloseBrace) {
+            self.blank(offset, offset + 1);
+            return true;
+        }
+
+        let is_at_end = self.text[offset.min(self.text.len())..].trim().is_empty();
+        let num_of_open_blocks = boundaries.iter().fold(0, |depth: usize, (_, boundary)| match boundary {
+            Boundary::OpenBrace => depth + 1,
+            Boundary::CloseBrace => depth.saturating_sub(1),
+            _ => depth,
+        });
+        if is_at_end && num_of_open_blocks > 0 {
+            // the closing braces go on a line of their own, past the end of the source.
+            self.text.push('\n');
+            self.text.push_str(&"}".repeat(num_of_open_blocks));
+            return true;
+        }
+        false
+    }
+
+    // Blanks out the statement ending at the given offset, which is missing its semicolon. Blanked
+    // out statements need none.
+    pub fn end_statement(&mut self, end: usize) -> bool {
+        let boundaries = boundaries_of(&self.text);
+        let start = statement_start(&self.text, end, &boundaries, &self.blanked);
+        if start == end {
+            return false;
+        }
+        self.blank(start, end);
+        true
+    }
+
+    // The first character of the program that stands in for blanked out source, outside of the
+    // ranges blanked out, string literals and comments.
+    pub fn stray_filler(&self) -> Option<usize> {
+        code_chars(&self.text)
+            .find(|(position, c)| is_filler(*c) && !self.is_blanked(*position))
+            .map(|(position, _)| position)
+    }
+
+    fn is_blanked(&self, position: usize) -> bool {
+        self.blanked.iter().any(|(start, end)| (*start..*end).contains(&position))
+    }
+
+    // Line breaks are kept, so that lines and columns stay where they are.
+    fn blank(&mut self, start: usize, end: usize) {
+        let blanked: String = self.text[start..end].chars()
+            .map(|c| match c {
+                '\n' => '\n',
+                c => filler(c),
+            })
+            .collect();
+        self.text.replace_range(start..end, &blanked);
+        self.blanked.push((start, end));
+    }
+}
+
+// The statement the error is in: from the boundary before the error, to the end of the statement
+// after it. Braces opened after the error are skipped over together with their blocks, which is
+// how a function with an error in its signature is blanked out as a whole. The error may also be in
+// the value of a let declaration, in which case only the value is blanked out, so that the variable
+// is still declared.
+fn region_of(text: &str, offset: usize, boundaries: &[(usize, Boundary)], blanked: &[(usize, usize)]) -> (usize, usize) {
+    let offset = offset.min(text.len());
+    let start = statement_start(text, offset, boundaries, blanked);
+
+    let mut end = text.len();
+    let mut is_terminated = false;
+    let mut depth = 0;
+    for (position, boundary) in boundaries.iter().filter(|(position, _)| *position >= offset) {
+        match boundary {
+            Boundary::Semicolon if depth == 0 => {
+                end = position + 1;
+                is_terminated = true;
+                break;
+            },
+            Boundary::OpenBrace => depth += 1,
+            Boundary::CloseBrace if depth == 0 => {
+                end = *position;
+                break;
+            },
+            Boundary::CloseBrace => {
+                depth -= 1;
+                // a block ends the statement, unless an `else` or a semicolon follows it.
+                let rest = text[position + 1..].trim_start();
+                if depth == 0 && !rest.starts_with("else") {
+                    is_terminated = rest.starts_with(';');
+                    end = match is_terminated {
+                        true => text.len() - rest.len() + 1,
+                        false => position + 1,
+                    };
+                    break;
+                }
+            },
+            Boundary::Fn if depth == 0 && *position > start => {
+                end = *position;
+                break;
+            },
+            _ => (),
+        }
+    }
+    let end = start.max(end - (text[start..end].len() - text[start..end].trim_end().len()));
+
+    if is_terminated {
+        if let Some(value_start) = let_value_start(&text[start..offset]) {
+            let value_start = start + value_start;
+            let value = &text[value_start..end - 1];
+            let value_start = value_start + (value.len() - value.trim_start().len());
+            let value_end = value_start.max(end - 1 - (value.len() - value.trim_end().len()));
+            if value_start < value_end && value_start <= offset {
+                return (value_start, value_end);
+            }
+        }
+    }
+    (start, end)
+}
+
+// The start of the statement the offset is in, which is past the boundary before it, and past the
+// statements blanked out before it.
+fn statement_start(text: &str, offset: usize, boundaries: &[(usize, Boundary)], blanked: &[(usize, usize)]) -> usize {
+    let start = boundaries.iter().rev()
+        .find(|(position, boundary)| *position < offset || (*boundary == Boundary::Fn && *position == offset))
+        .map_or(0, |(position, boundary)| match boundary {
+            Boundary::Fn => *position,
+            _ => *position + 1,
+        });
+    let start = blanked.iter()
+        .map(|(_, end)| *end)
+        .filter(|end| *end <= offset)
+        .fold(start, usize::max);
+    start + (text[start..offset].len() - text[start..offset].trim_start().len())
+}
+
+// Where the value of the let declaration starts, just past its `=`.
+fn let_value_start(statement: &str) -> Option<usize> {
+    if !statement.starts_with("let ") {
+        return None;
+    }
+    let bytes = statement.as_bytes();
+    (0..bytes.len())
+        .find(|index| bytes[*index] == b'='
+            && bytes.get(index + 1) != Some(&b'=')
+            && !(*index > 0 && b"=!<>+-*/%".contains(&bytes[index - 1])))
+        .map(|index| index + 1)
+}
+
+// The boundaries in the source, leaving out those within string literals and comments.
+fn boundaries_of(text: &str) -> Vec<(usize, Boundary)> {
+    let mut boundaries = vec![];
+    let mut previous: Option<char> = None;
+    for (position, c) in code_chars(text) {
+        match c {
+            ';' => boundaries.push((position, Boundary::Semicolon)),
+            '{' => boundaries.push((position, Boundary::OpenBrace)),
+            '}' => boundaries.push((position, Boundary::CloseBrace)),
+            'f' if !previous.is_some_and(is_name_char) && text[position..].starts_with("fn")
+                && text[position + 2..].starts_with(char::is_whitespace) =>
+                boundaries.push((position, Boundary::Fn)),
+            _ => (),
+        }
+        previous = Some(c);
+    }
+    boundaries
+}
+
+// The characters of the source outside of string literals and comments, with their positions.
+fn code_chars(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || loop {
+        let (position, c) = chars.next()?;
+        match c {
+            '"' => {
+                let mut is_escaped = false;
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        '\\' if !is_escaped => is_escaped = true,
+                        '"' if !is_escaped => break,
+                        _ => is_escaped = false,
+                    }
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            },
+            '/' if matches!(chars.peek(), Some((_, '*'))) => {
+                chars.next();
+                let mut previous = ' ';
+                for (_, c) in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            },
+            c => return Some((position, c)),
+        }
+    })
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// The characters standing in for the blanked out source, as matched by `error_filler` in the grammar.
+fn filler(c: char) -> char {
+    match c.len_utf8() {
+        1 => '\u{1}',
+        2 => '\u{80}',
+        3 => '\u{FFFF}',
+        _ => '\u{10FFFF}',
+    }
+}
+
+fn is_filler(c: char) -> bool {
+    c == filler(c)
+}
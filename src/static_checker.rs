@@ -1,164 +1,1306 @@
 #![allow(dead_code)]
+mod borrows;
+mod initialization;
+mod moves;
+
 use crate::parser::ast::
 {AST, Stmt, Block, Sequence, SequenceStmt, FuncParameter, Expr, DataType, Literal, 
-    PrimitiveOperation, UnaryOperator, BinaryOperator};
-use std::collections::{HashMap, LinkedList};
-
-pub fn check(parsed_stmt : & Vec<Stmt>) -> ExpiredLifetimes{
-    let mut d_f_table : HashMap<&'static str, FunctionStore> = HashMap::new();
-    let mut scope : LinkedList<DecAndBorrowStack> = LinkedList::new();
-    let mut v_table : HashMap<&'static str, VariableProperties> = HashMap::new();
-    let mut exp_lt : ExpiredLifetimes = ExpiredLifetimes::new();
+    PrimitiveOperation, UnaryOperator, BinaryOperator, PrimitiveOperator, VariadicOperator, Span,
+    LifetimeParameter, binding_name, source_name, is_elided_lifetime, is_explicit_return};
+use crate::format_string;
+use crate::diagnostic::Diagnostic;
+use std::collections::{HashMap, HashSet, LinkedList};
+use borrows::{BorrowEvent, BorrowKind, FunctionBorrows, LoanSource, Loans, Place};
+
+// Checking goes on after an error, so that all the errors of the program are reported at once.
+pub fn check(parsed_stmt : & Vec<Stmt>) -> Result<ExpiredLifetimes, Vec<Diagnostic>> {
     let mut env = Environment{
-        declared_functions_table : d_f_table,
-        scope_info : scope,
-        variables_table : v_table,
-        expired_lifetimes : exp_lt,
+        declared_functions_table : HashMap::new(),
+        declared_statics_table : HashMap::new(),
+        scope_info : LinkedList::new(),
+        variables_table : HashMap::new(),
+        expired_lifetimes : ExpiredLifetimes::default(),
+        enclosing_loops : LinkedList::new(),
+        enclosing_function : None,
+        has_temporaries : false,
+        borrows : FunctionBorrows::default(),
+        diagnostics : vec![],
     };
-    // let all be in the main function.
-    let main_fn : &Stmt = & (*parsed_stmt)[0];
-    type_statement(main_fn, &mut env);
-    return env.expired_lifetimes;
+    // The signatures of all functions and the statics are collected first, so that functions can
+    // be used before their declaration and can be mutually recursive.
+    enter_scope(&mut env);
+    for stmt in parsed_stmt {
+        if is_function_declaration(stmt) {
+            declare_function(stmt, &mut env);
+        } else if is_static_statement(stmt) {
+            type_statement(stmt, &mut env);
+        }
+    }
+    for stmt in parsed_stmt {
+        if is_function_declaration(stmt) {
+            check_function_sanity(stmt, &mut env);
+        }
+    }
+    if env.diagnostics.iter().any(Diagnostic::is_error) {
+        return Err(env.diagnostics);
+    }
+    Ok(env.expired_lifetimes)
+}
+
+// What the compiler needs to know about ownership, keyed by the byte ranges of positions in the
+// source. Variables are dropped at the closing braces of their scopes, when breaking out of or
+// continuing the loops they are declared in, when returning from their function, and when
+// assigned to. A moved variable is left without a value, so dropping it again does nothing.
+// Temporaries are dropped at the end of the statement they are created in.
+#[derive(Default)]
+pub struct ExpiredLifetimes {
+    pub drops : HashMap<(usize, usize), Vec<String>>,
+    // the uses of variables that move their values out.
+    pub moves : HashSet<(usize, usize)>,
+    // the values that are only read, rather than moved into a variable or out of a block.
+    pub temporaries : HashSet<(usize, usize)>,
+    // the statements, and function bodies, that end by dropping their temporaries.
+    pub temporary_drops : HashSet<(usize, usize)>,
 }
 
-type ExpiredLifetimes = HashMap<usize, Vec<String>>;
 type FunctionStore = (Vec<DataType>, DataType);
-type DecAndBorrowStack = (Vec<&'static str>, LinkedList<&'static str>);
+type DecAndBorrowStack = (Vec<String>, LinkedList<String>);
 struct Environment {
-    declared_functions_table : HashMap<&'static str, FunctionStore>,
+    declared_functions_table : HashMap<String, FunctionStore>,
+    // statics are never moved out of or dropped, so only their type and mutability are kept.
+    declared_statics_table : HashMap<String, (DataType, bool)>,
+    // innermost scope first. holds the bindings declared in each scope, in order of declaration.
     scope_info: LinkedList<DecAndBorrowStack>,
-    variables_table : HashMap<&'static str, VariableProperties>,
+    // keyed by binding, as a name may be declared again to shadow the earlier variable.
+    variables_table : HashMap<String, VariableProperties>,
     expired_lifetimes : ExpiredLifetimes,
+    enclosing_loops : LinkedList<LoopProperties>,
+    enclosing_function : Option<FunctionProperties>,
+    // whether the statement being checked has temporaries to drop at its end.
+    has_temporaries : bool,
+    borrows : FunctionBorrows,
+    diagnostics : Vec<Diagnostic>,
+}
+
+struct FunctionProperties {
+    return_type : DataType,
+    // the points of the borrow events that `return` leaves the function from, and the loans held
+    // by the values returned with.
+    returns : Vec<usize>,
+    return_loans : Loans,
+}
+
+struct LoopProperties {
+    label : Option<String>,
+    // type of the values broken out of the loop with, if any.
+    break_type : Option<DataType>,
+    // loans held by the values broken out of the loop with.
+    break_loans : Loans,
+    is_while : bool,
+    // the number of scopes entered outside of the loop, whose variables outlive breaking out of it.
+    scope_depth : usize,
+    // the points of the borrow events that control flows back to when continuing, and from when
+    // leaving the loop.
+    head : usize,
+    exits : Vec<usize>,
 }
 
 struct VariableProperties{
-    own_type : DataType,
+    // None until the first assignment to a variable declared without a value nor a type.
+    own_type : Option<DataType>,
     mutability : bool,
     is_copy_trait_mem : bool,
-    // not accounting for immutable borrows for now
-    // immutable borrows require check on mutability bool. nothing additional.
+    declared_at : Span,
+    is_parameter : bool,
+    // declared without a value, so that it can be assigned to once even if immutable.
+    is_deferred : bool,
 }
 
-fn check_duplicate(name : & String, env : &mut Environment) {
-    for (fn_name, value) in env.declared_functions_table.iter() {
-        if (*fn_name).eq(name.as_str()) {
-            panic!("Duplicate name: {} already declared", name);
-        }
+fn check_duplicate(name : & String, position : Span, env : &mut Environment) {
+    if env.declared_functions_table.contains_key(name) || env.declared_statics_table.contains_key(name) {
+        env.diagnostics.push(Diagnostic::error(format!("the name `{}` is defined multiple times", name), position,
+            &format!("`{}` redefined here", name))
+            .with_code("E0428")
+            .with_note(&format!("`{}` must be defined only once in this scope", name)));
+    }
+}
+
+// Variables may shadow each other and functions, but not statics.
+fn check_shadowing(name : & String, position : Span, env : &mut Environment) {
+    if env.declared_statics_table.contains_key(name) {
+        env.diagnostics.push(Diagnostic::error(String::from("let bindings cannot shadow statics"), position,
+            "cannot be named the same as a static")
+            .with_code("E0530"));
     }
-    for (var_name, value) in env.variables_table.iter() {
-        if (*var_name).eq(name.as_str()) {
-            panic!("Duplicate name: {} already declared", name);
+}
+
+// The binding of the innermost variable declared with the name. Statics and functions are not
+// bound in scopes, so their names are kept.
+fn resolve_name(name : &str, env : & Environment) -> String {
+    for (declared_names, _) in &env.scope_info {
+        for binding in declared_names.iter().rev() {
+            if source_name(binding) == name && env.variables_table.contains_key(binding) {
+                return binding.clone();
+            }
         }
     }
+    String::from(name)
+}
+
+fn insert_expired_lifetime(env : &mut Environment, position : Span, var_name : &str) {
+    env.expired_lifetimes.drops.entry(position.byte_range).or_default().push(String::from(var_name));
+}
+
+// Reading a variable requires the loans held by its value, which are also held by the value read.
+fn update_scope_with_use(env : &mut Environment, var_name : &str, position : Span) -> Loans {
+    let loans = variable_loans(var_name, env);
+    if !loans.is_empty() {
+        env.borrows.record(BorrowEvent::Read(variable_place(var_name), position));
+        env.borrows.record_use(&loans, position);
+    }
+    loans
 }
 
-fn insert_expired_lifetime(env : &mut Environment, line_no : usize, var_name : &str) {
-    match env.expired_lifetimes.get_mut(&line_no) {
-        Some(vars) => {let o = vars.push(String::from(var_name));
+// statics and functions hold no loans.
+fn variable_loans(var_name : &str, env : &Environment) -> Loans {
+    match env.variables_table.contains_key(var_name) {
+        true => vec![LoanSource::Variable(String::from(var_name))],
+        false => vec![],
+    }
+}
+
+fn variable_place(var_name : &str) -> Place {
+    Place { variable : String::from(var_name), num_of_derefs : 0 }
+}
+
+// Only variables and dereferences of places can be borrowed from, anything else is a temporary.
+fn place_of(expr : & Expr, env : & Environment) -> Option<Place> {
+    match expr {
+        Expr::IdentifierExpr(name, _) => Some(variable_place(&resolve_name(name, env))),
+        Expr::PrimitiveOperationExpr(operation, _) => match operation.as_ref() {
+            PrimitiveOperation::UnaryOperation { operator : UnaryOperator::Dereference, operand } =>
+                place_of(operand, env).map(|place| Place { num_of_derefs : place.num_of_derefs + 1, ..place }),
+            _ => None,
         },
-        None => {let o = env.expired_lifetimes.insert(line_no, vec![String::from(var_name)]);
+        _ => None,
+    }
+}
+
+fn update_scope_with_drop(env : &mut Environment, var_name : &str, position : Span) {
+    env.borrows.record(BorrowEvent::StorageDead(String::from(var_name), position));
+}
+
+fn enter_scope(env : &mut Environment) {
+    env.scope_info.push_front((vec![], LinkedList::new()));
+}
+
+// Variables are dropped at the closing brace of their scope, in the reverse order of their
+// declaration.
+fn exit_scope(closing_brace : Span, env : &mut Environment) {
+    let (declared_names, _) = env.scope_info.pop_front().unwrap();
+    for name in declared_names.iter().rev() {
+        let properties = match env.variables_table.remove(name) {
+            Some(properties) => properties,
+            None => { // a function declared within the scope.
+                env.declared_functions_table.remove(name);
+                continue;
+            },
+        };
+        let own_type = match &properties.own_type {
+            Some(own_type) => own_type,
+            None => {
+                env.diagnostics.push(Diagnostic::error(String::from("type annotations needed"), properties.declared_at, "")
+                    .with_code("E0282")
+                    .with_help(&format!("consider giving `{}` an explicit type", source_name(name))));
+                continue;
+            },
+        };
+        if needs_drop(own_type) {
+            insert_expired_lifetime(env, closing_brace, name);
+        }
+        env.borrows.declarations.insert(name.clone(), (own_type.clone(), properties.declared_at));
+        update_scope_with_drop(env, name, closing_brace);
+    }
+}
+
+fn set_variable(name : &str, own_type : DataType, mutability : bool, declared_at : Span, loans : Loans, env : &mut Environment) {
+    bind_variable(name, Some(own_type), mutability, declared_at, false, env);
+    env.borrows.record(BorrowEvent::Hold(name.to_string(), loans));
+}
+
+// Declares a variable without a value, whose type is taken from the first assignment to it unless
+// annotated.
+fn declare_variable(name : &str, annotation : Option<DataType>, mutability : bool, declared_at : Span, env : &mut Environment) {
+    bind_variable(name, annotation, mutability, declared_at, true, env);
+    env.borrows.record(BorrowEvent::StorageLive(name.to_string(), mutability, declared_at));
+}
+
+fn bind_variable(name : &str, own_type : Option<DataType>, mutability : bool, declared_at : Span, is_deferred : bool, env : &mut Environment) {
+    let properties = VariableProperties {
+        is_copy_trait_mem : own_type.as_ref().is_some_and(is_copy_type),
+        own_type,
+        mutability,
+        declared_at,
+        is_parameter : false,
+        is_deferred,
+    };
+    env.variables_table.insert(name.to_owned(), properties);
+    env.scope_info.front_mut().unwrap().0.push(name.to_owned());
+}
+
+fn set_function(function_name : &str, param_types : Vec<DataType>, return_type : DataType, env : &mut Environment) {
+    env.declared_functions_table.insert(function_name.to_owned(), (param_types, return_type));
+}
+
+fn lookup_type(name : & String, position : Span, env : &mut Environment) -> DataType {
+    if let Some(properties) = env.variables_table.get_mut(&resolve_name(name, env)) {
+        match &properties.own_type {
+            Some(own_type) => return own_type.clone(),
+            // not assigned to anywhere before, so not on the way here either. Its type is unknown
+            // from then on, so that it is not reported again.
+            None => {
+                properties.own_type = Some(DataType::Unknown);
+                let diagnostic = Diagnostic::error(format!("used binding `{}` isn't initialized", name), position,
+                    &format!("`{}` used here but it isn't initialized", name))
+                    .with_code("E0381")
+                    .with_label(properties.declared_at, "binding declared here but left uninitialized")
+                    .with_help(&format!("consider assigning a value: `let {} = ...;`", name));
+                env.diagnostics.push(diagnostic);
+                return DataType::Unknown;
+            },
+        }
+    }
+    if let Some((own_type, _)) = env.declared_statics_table.get(name) {
+        return own_type.clone();
+    }
+    match env.declared_functions_table.get(name) {
+        Some((param_types, return_type)) =>
+            DataType::Func(vec![], param_types.clone(), Box::new(return_type.clone())),
+        None => {
+            env.diagnostics.push(Diagnostic::error(format!("cannot find value `{}` in this scope", name), position,
+                "not found in this scope")
+                .with_code("E0425"));
+            DataType::Unknown
         },
     }
 }
 
-fn update_scope_with_use(env : &mut Environment, var_name : &str) {
+// Using a value that does not implement Copy moves ownership out of its place. Only variables can
+// be moved out of, as the value behind a reference is owned by someone else.
+fn handle_stack(rhs : &Expr, rhs_type : &DataType, env : &mut Environment) {
+    if !needs_drop(rhs_type) {
+        return;
+    }
+    let position = rhs.get_span();
+    match place_of(rhs, env) {
+        Some(place) if place.num_of_derefs > 0 => {
+            let reference_kind = match env.variables_table.get(&place.variable) {
+                Some(VariableProperties { own_type : Some(own_type), .. }) => match dereference_type(own_type, place.num_of_derefs - 1) {
+                    DataType::MutRef(..) => "a mutable reference",
+                    _ => "a shared reference",
+                },
+                _ => "a reference",
+            };
+            env.diagnostics.push(Diagnostic::error(format!("cannot move out of `{}` which is behind {}", place, reference_kind), position,
+                &format!("move occurs because `{}` has type `{}`, which does not implement the `Copy` trait", place, rhs_type))
+                .with_code("E0507"));
+        },
+        Some(place) if env.variables_table.contains_key(&place.variable) => {
+            env.expired_lifetimes.moves.insert(position.byte_range);
+            env.borrows.record(BorrowEvent::Move(place, position));
+        },
+        _ => (),
+    }
+}
 
+// A value that is only read is a temporary, unless it is read from a place, and is dropped at the
+// end of its statement.
+fn handle_temporary(expr : &Expr, expr_type : &DataType, env : &mut Environment) {
+    if needs_drop(expr_type) && place_of(expr, env).is_none() {
+        env.expired_lifetimes.temporaries.insert(expr.get_span().byte_range);
+        env.has_temporaries = true;
+    }
 }
 
-fn update_scope_with_drop(env : &mut Environment, var_name : &str) {
+// The temporaries of a statement are dropped once it is done.
+fn drop_temporaries(position : Span, env : &mut Environment) {
+    if std::mem::take(&mut env.has_temporaries) {
+        env.expired_lifetimes.temporary_drops.insert(position.byte_range);
+    }
+}
 
+fn dereference_type(datatype : &DataType, num_of_derefs : usize) -> &DataType {
+    match datatype {
+        DataType::Ref(_, datatype) | DataType::MutRef(_, datatype) if num_of_derefs > 0 =>
+            dereference_type(datatype, num_of_derefs - 1),
+        _ => datatype,
+    }
 }
 
-fn handle_stack(rhs : &Expr, env : &mut Environment) {
-    if is_identifier_expression(rhs) {
-        let name = identifier(rhs).as_str();
-        let rhs_datatype = & env.variables_table.get(name).unwrap().own_type;
-        if is_mem_type(rhs_datatype) { // rhs is some memory
-            //drop type. then remove RHS.
-            if !is_copy_type(rhs_datatype) {
-                insert_expired_lifetime(env, rhs.get_source_location().line, identifier(rhs));
+// Assigning to a place, or borrowing it mutably, requires its variable to be declared as mutable
+// if the place is the variable itself, and every reference the place goes through to be mutable
+// otherwise.
+fn check_mutable_place(place : & Place, is_assignment : bool, position : Span, env : &mut Environment) {
+    let name = source_name(&place.variable);
+    let own_type = match env.variables_table.get(&place.variable) {
+        Some(properties) if place.num_of_derefs == 0 => {
+            // assigning to a variable declared without a value more than once is found once the flow
+            // of the function is known.
+            if properties.mutability || (is_assignment && properties.is_deferred) {
+                return;
             }
+            let diagnostic = match (is_assignment, properties.is_parameter) {
+                (true, true) => Diagnostic::error(format!("cannot assign to immutable argument `{}`", name), position,
+                    "cannot assign to immutable argument")
+                    .with_code("E0384"),
+                (true, false) => Diagnostic::error(format!("cannot assign twice to immutable variable `{}`", name), position,
+                    "cannot assign twice to immutable variable")
+                    .with_code("E0384")
+                    .with_label(properties.declared_at, &format!("first assignment to `{}`", name)),
+                (false, _) => Diagnostic::error(format!("cannot borrow `{}` as mutable, as it is not declared as mutable", name), position,
+                    "cannot borrow as mutable")
+                    .with_code("E0596"),
+            };
+            env.diagnostics.push(diagnostic.with_suggestion("consider making this binding mutable", properties.declared_at, &format!("mut {}", name)));
+            return;
+        },
+        Some(properties) => match &properties.own_type {
+            Some(own_type) => own_type.clone(),
+            None => return,
+        },
+        None => {
+            let diagnostic = match env.declared_statics_table.get(name) {
+                Some((_, true)) => return,
+                Some((_, false)) if is_assignment => Diagnostic::error(format!("cannot assign to immutable static item `{}`", name), position,
+                    "cannot assign")
+                    .with_code("E0594"),
+                Some((_, false)) => Diagnostic::error(format!("cannot borrow immutable static item `{}` as mutable", name), position,
+                    "cannot borrow as mutable")
+                    .with_code("E0596"),
+                None => return, // functions are not places.
+            };
+            env.diagnostics.push(diagnostic);
+            return;
+        },
+    };
+
+    for num_of_derefs in 0..place.num_of_derefs {
+        if let DataType::Ref(_, referenced_type) = dereference_type(&own_type, num_of_derefs) {
+            let help = format!("consider changing this binding's type to be a mutable reference: `&mut {}`", referenced_type);
+            let diagnostic = match is_assignment {
+                true => Diagnostic::error(format!("cannot assign to `{}`, which is behind a `&` reference", place), position,
+                    &format!("`{}` is a `&` reference, so the data it refers to cannot be written", name))
+                    .with_code("E0594"),
+                false => Diagnostic::error(format!("cannot borrow `{}` as mutable, as it is behind a `&` reference", place), position,
+                    &format!("`{}` is a `&` reference, so the data it refers to cannot be borrowed as mutable", name))
+                    .with_code("E0596"),
+            };
+            env.diagnostics.push(diagnostic.with_help(&help));
+            return;
         }
     }
 }
+
 fn type_statement(stmt : &  Stmt, env : &mut  Environment)  -> DataType {
     if is_let_statement(stmt) {
         let name = let_statement_name(stmt);
-        check_duplicate(name, env); // check for duplicate in scope.
+        check_shadowing(name, let_statement_name_position(stmt), env);
+        let binding = binding_name(name, let_statement_name_position(stmt).start);
+        let mutability = is_mutable_let_statement(stmt);
 
-        let rhs = non_optional_value(let_statement_value(stmt));
-        handle_stack(rhs, env); // handle right hand side uses only. MODIFIES STACK
+        let rhs = match let_statement_value(stmt) {
+            Some(rhs) => rhs,
+            None => {
+                declare_variable(&binding, let_statement_type(stmt).clone(), mutability, let_statement_name_position(stmt), env);
+                return DataType::Unit;
+            },
+        };
+        let (value_type, value_loans) = type_expression_with_loans(rhs, env);
+        handle_stack(rhs, &value_type, env); // handle right hand side uses only. MODIFIES STACK
 
-        let type_of_variable = type_expression(non_optional_value(let_statement_value(stmt)), env);
-        //let degree = degree(non_optional_value(let_statement_value(stmt)), env);
-        let mutability = is_mutable_let_statement(stmt);
-       // set_variable(name, *type_of_variable, mutability, degree);  // updates stack. MODIFIES STACK
-        
+        let type_of_variable = match let_statement_type(stmt) {
+            Some(annotation) => {
+                check_assignable(&value_type, annotation, rhs.get_span(), env);
+                annotation.clone()
+            },
+            None => value_type,
+        };
+        // the variable is only bound after its value, which still sees any variable it shadows.
+        set_variable(&binding, type_of_variable, mutability, let_statement_name_position(stmt), value_loans, env);  // updates stack. MODIFIES STACK
+
+        DataType::Unit
+    } else if is_static_statement(stmt) {
+        let name = static_statement_name(stmt);
+        check_duplicate(name, static_statement_name_position(stmt), env);
+
+        let value = static_statement_value(stmt);
+        let annotation = static_statement_type(stmt);
+        check_assignable(&type_expression(value, env), annotation, value.get_span(), env);
+        env.declared_statics_table.insert(name.clone(), (annotation.clone(), is_mutable_static_statement(stmt)));
         return DataType::Unit;
     } else if is_function_declaration(stmt) {
-        let function_name = function_declaration_name(stmt);
-       // check_duplicate(function_name, env);      // no overloading allowed
-       // check_function_sanity(stmt, env); // checks on block sanity. + return type consistency
-        
-        let params = function_declaration_parameters(stmt);
-        let return_type = function_declaration_return_type(stmt); // Unit if returns nothing.
-
-        //set_function(function_name, params, return_type, env);
+        // the signature is already declared by the enclosing sequence.
+        check_function_sanity(stmt, env); // checks on block sanity. + return type consistency
         return DataType::Unit;
     } else if is_expression_statement(stmt) {
-        return type_expression(expression_statement(stmt), env);
+        let expr = expression_statement(stmt);
+        let expr_type = type_expression(expr, env);
+        handle_temporary(expr, &expr_type, env); // the value of an expression statement is discarded.
+        return expr_type;
     } else {  
         return DataType::Unit;
     }
 }
 
-//fn set_function(function_name : & String,
-  //   params : & Vec<FuncParameter>, return_type : & DataType, env : &mut Environment) {
-    //     env.
+fn declare_function(stmt : & Stmt, env : &mut Environment) {
+    let function_name = function_declaration_name(stmt);
+    check_duplicate(function_name, function_declaration_name_position(stmt), env);      // no overloading allowed
+    check_function_signature(stmt, env);
+
+    let (param_types, return_type) = elided_signature(stmt, &mut env.diagnostics); // Unit if returns nothing.
+    set_function(function_name, param_types, return_type, env);
+    env.scope_info.front_mut().unwrap().0.push(function_name.clone());
+}
+
+// Lifetimes in a signature must be declared, and a returned reference must name the lifetime of
+// the data it borrows, since it can only borrow from the parameters or from statics.
+fn check_function_signature(stmt : & Stmt, env : &mut Environment) {
+    let declared_lifetimes = function_declaration_lifetime_parameters(stmt);
+    let return_type = function_declaration_return_type(stmt);
+    let param_types = function_declaration_parameters(stmt).iter().map(|(_, param_type, _, _)| param_type);
+    for datatype in param_types.chain(std::iter::once(return_type)) {
+        for lifetime in lifetimes_of(datatype).into_iter().flatten() {
+            if lifetime != "'static" && !declared_lifetimes.contains(lifetime) {
+                env.diagnostics.push(Diagnostic::error(format!("use of undeclared lifetime name `{}`", lifetime), function_declaration_name_position(stmt),
+                    "undeclared lifetime")
+                    .with_code("E0261")
+                    .with_help(&format!("consider introducing lifetime `{}` in the signature of `{}`", lifetime, function_declaration_name(stmt))));
+            }
+        }
+    }
+}
+
+// The signature of a function with the lifetimes elided from it filled in, as in Rust: every elided
+// lifetime of a parameter is a lifetime of its own, and those of the return type are the lifetime
+// of the parameters if there is exactly one. Rust's third rule, which prefers the lifetime of
+// `&self`, does not apply as there are no methods. If they cannot be elided, the signature is taken
+// as written, without the lifetimes.
+fn elided_signature(stmt : & Stmt, diagnostics : &mut Vec<Diagnostic>) -> (Vec<DataType>, DataType) {
+    let param_types : Vec<DataType> = function_declaration_parameters(stmt).iter().map(|(_, param_type, _, _)| param_type.clone()).collect();
+    let return_type = function_declaration_return_type(stmt);
+    if let Some(signature) = elide_lifetimes(&param_types, return_type) {
+        return signature;
+    }
+    let note = match param_types.iter().any(|param_type| !lifetimes_of(param_type).is_empty()) {
+        true => "this function's return type contains a borrowed value, but the signature does not say which one of the parameters it is borrowed from",
+        false => "this function's return type contains a borrowed value, but there is no value for it to be borrowed from",
+    };
+    let (help, signature) = suggested_signature(stmt);
+    diagnostics.push(Diagnostic::error(String::from("missing lifetime specifier"), function_declaration_name_position(stmt),
+        "expected named lifetime parameter")
+        .with_code("E0106")
+        .with_note(note)
+        .with_help(&format!("consider {}: `{}`", help, signature)));
+    (param_types, return_type.clone())
+}
+
+// None if the lifetimes of the return type cannot be elided.
+fn elide_lifetimes(param_types : & [DataType], return_type : & DataType) -> Option<(Vec<DataType>, DataType)> {
+    let mut num_of_elided = 0;
+    let mut elided_param_types = vec![];
+    for param_type in param_types {
+        elided_param_types.push(fill_elided_lifetimes(param_type, &mut || {
+            num_of_elided += 1;
+            Some(format!("'_{}", num_of_elided))
+        })?);
+    }
+
+    let mut input_lifetimes : Vec<&LifetimeParameter> = vec![];
+    for lifetime in elided_param_types.iter().flat_map(lifetimes_of).flatten() {
+        if !input_lifetimes.contains(&lifetime) {
+            input_lifetimes.push(lifetime);
+        }
+    }
+    let elided_return_type = fill_elided_lifetimes(return_type, &mut || match input_lifetimes.as_slice() {
+        [lifetime] => Some(lifetime.to_string()),
+        _ => None,
+    })?;
+    Some((elided_param_types, elided_return_type))
+}
+
+// Replaces the lifetimes missing from the references of the type with those given by the closure.
+// Function types elide the lifetimes of their own signature.
+fn fill_elided_lifetimes<F>(datatype : & DataType, elided_lifetime : &mut F) -> Option<DataType>
+    where F : FnMut() -> Option<LifetimeParameter> {
+    match datatype {
+        DataType::Ref(lifetime, datatype) => {
+            let lifetime = match lifetime {
+                Some(lifetime) => lifetime.clone(),
+                None => elided_lifetime()?,
+            };
+            Some(DataType::Ref(Some(lifetime), Box::new(fill_elided_lifetimes(datatype, elided_lifetime)?)))
+        },
+        DataType::MutRef(lifetime, datatype) => {
+            let lifetime = match lifetime {
+                Some(lifetime) => lifetime.clone(),
+                None => elided_lifetime()?,
+            };
+            Some(DataType::MutRef(Some(lifetime), Box::new(fill_elided_lifetimes(datatype, elided_lifetime)?)))
+        },
+        DataType::Func(lifetime_params, param_types, return_type) => {
+            let (param_types, return_type) = elide_lifetimes(param_types, return_type)?;
+            Some(DataType::Func(lifetime_params.clone(), param_types, Box::new(return_type)))
+        },
+        datatype => Some(datatype.clone()),
+    }
+}
+
+// Names the lifetimes missing from a signature whose return type cannot be elided: `'static` if
+// no parameter is a reference, and a new lifetime parameter otherwise.
+fn suggested_signature(stmt : & Stmt) -> (&'static str, String) {
+    let params = function_declaration_parameters(stmt);
+    let has_input_lifetimes = params.iter().any(|(_, param_type, _, _)| !lifetimes_of(param_type).is_empty());
+    let (help, lifetime) = match has_input_lifetimes {
+        true => ("introducing a named lifetime parameter", "'a"),
+        false => ("using the `'static` lifetime", "'static"),
+    };
+    let mut name_lifetime = || Some(String::from(lifetime));
+    let mut lifetime_params = function_declaration_lifetime_parameters(stmt).clone();
+    if has_input_lifetimes {
+        lifetime_params.push(String::from(lifetime));
+    }
+    let params : Vec<String> = params.iter().map(|(param, param_type, is_mutable, _)|
+        format!("{}{}: {}", if *is_mutable { "mut " } else { "" }, identifier(param), fill_elided_lifetimes(param_type, &mut name_lifetime).unwrap())).collect();
+    let return_type = fill_elided_lifetimes(function_declaration_return_type(stmt), &mut name_lifetime).unwrap();
+    let lifetime_params = match lifetime_params.is_empty() {
+        true => String::new(),
+        false => format!("<{}>", lifetime_params.join(", ")),
+    };
+    (help, format!("fn {}{}({}) -> {}", function_declaration_name(stmt), lifetime_params, params.join(", "), return_type))
+}
+
+// The lifetimes of all references within the type, None for those without an annotation.
+fn lifetimes_of(datatype : & DataType) -> Vec<Option<&LifetimeParameter>> {
+    match datatype {
+        DataType::Ref(lifetime, datatype) | DataType::MutRef(lifetime, datatype) => {
+            let mut lifetimes = vec![lifetime.as_ref()];
+            lifetimes.extend(lifetimes_of(datatype));
+            lifetimes
+        },
+        _ => vec![], // the lifetimes of function types belong to their own signature.
+    }
+}
+
+// A reference parameter holds one loan of the caller per lifetime within its type.
+fn parameter_loans(param_name : &str, param_type : & DataType, position : Span, env : &mut Environment) -> Loans {
+    let mut loans = vec![];
+    for lifetime in lifetimes_of(param_type) {
+        let place = Place { variable : param_name.to_owned(), num_of_derefs : 1 };
+        loans.push(LoanSource::Loan(env.borrows.create_parameter_loan(place, position, lifetime.cloned())));
+    }
+    loans
+}
+
+// A function body only sees its parameters, the functions and the statics, so the variables and
+// loops of the enclosing function are set aside while it is checked. Its borrows are only checked
+// if it type checks, including its signature, which is reported when the function is declared.
+fn check_function_sanity(stmt : & Stmt, env : &mut Environment) {
+    let enclosing_variables = std::mem::take(&mut env.variables_table);
+    let enclosing_scopes = std::mem::take(&mut env.scope_info);
+    let enclosing_loops = std::mem::take(&mut env.enclosing_loops);
+    let enclosing_borrows = std::mem::take(&mut env.borrows);
+    let enclosing_temporaries = std::mem::take(&mut env.has_temporaries);
+    let num_of_diagnostics = env.diagnostics.len();
+    let mut signature_diagnostics = vec![];
+    let (param_types, return_type) = elided_signature(stmt, &mut signature_diagnostics);
+    let enclosing_function = env.enclosing_function.replace(FunctionProperties {
+        return_type : return_type.clone(),
+        returns : vec![],
+        return_loans : vec![],
+    });
+    enter_scope(env);
+    for ((param, _, is_mutable, _), param_type) in function_declaration_parameters(stmt).iter().zip(param_types.iter()) {
+        let param_name = identifier(param);
+        if env.variables_table.contains_key(&resolve_name(param_name, env)) {
+            env.diagnostics.push(Diagnostic::error(format!("identifier `{}` is bound more than once in this parameter list", param_name),
+                param.get_span(), "used as parameter more than once")
+                .with_code("E0415"));
+        }
+        let binding = binding_name(param_name, param.get_source_location());
+        let loans = parameter_loans(&binding, param_type, param.get_span(), env);
+        set_variable(&binding, param_type.clone(), *is_mutable, param.get_span(), loans, env);
+        env.variables_table.get_mut(&binding).unwrap().is_parameter = true;
+    }
+
+    let body = function_declaration_body(stmt);
+    let (body_type, _, mut body_loans) = type_block(body, env);
+    drop_temporaries(body.get_span(), env); // those of the value of the body.
+    if !is_assignable(&body_type, &return_type) {
+        env.diagnostics.push(Diagnostic::error(String::from("mismatched types"), function_declaration_name_position(stmt),
+            &format!("expected `{}`, found `{}`", return_type, body_type))
+            .with_code("E0308")
+            .with_note(&format!("function `{}` is expected to return `{}`, but its body evaluates to `{}`",
+                function_declaration_name(stmt), return_type, body_type)));
+    }
+    exit_scope(body.get_span().end_point(), env);
+    // control flows out of the function from the end of its body, and from every `return`.
+    let properties = std::mem::replace(&mut env.enclosing_function, enclosing_function).unwrap();
+    let exit = env.borrows.branch_point();
+    for point in properties.returns {
+        env.borrows.add_jump(point, exit);
+    }
+    body_loans.extend(properties.return_loans);
+
+    // values are only moved out of variables whose types are known, so moves are checked anyway.
+    let is_well_typed = signature_diagnostics.is_empty() && env.diagnostics.len() == num_of_diagnostics;
+    moves::check_moves(&env.borrows, &mut env.diagnostics);
+    if is_well_typed {
+        initialization::check_initialization(&env.borrows, &mut env.diagnostics);
+        let analysis = borrows::analyse(&env.borrows);
+        check_returned_loans(&analysis.loans_at_exit(&body_loans), &return_type, env);
+        borrows::check_borrows(&env.borrows, &analysis, &mut env.diagnostics);
+    }
+
+    env.variables_table = enclosing_variables;
+    env.scope_info = enclosing_scopes;
+    env.enclosing_loops = enclosing_loops;
+    env.borrows = enclosing_borrows;
+    env.has_temporaries = enclosing_temporaries;
+}
+
+// The variables of a function, its parameters included, are dropped when it returns, so a returned
+// reference can only borrow from behind the reference parameters whose lifetimes it names, or from
+// statics.
+fn check_returned_loans(loans : & Vec<usize>, return_type : & DataType, env : &mut Environment) {
+    let return_lifetimes = lifetimes_of(return_type);
+    let expected_lifetime = return_lifetimes.iter().flatten().next().map_or("'static", |lifetime| lifetime.as_str());
+    for &index in loans {
+        let loan = &env.borrows.loans[index];
+        let name = source_name(&loan.place.variable);
+        if loan.is_parameter {
+            let explicit_lifetime_required = Diagnostic::error(format!("explicit lifetime required in the type of `{}`", name), loan.position,
+                &format!("lifetime `{}` required", expected_lifetime))
+                .with_code("E0621");
+            match &loan.lifetime {
+                Some(lifetime) if lifetime == "'static" || return_lifetimes.contains(&Some(lifetime)) => (),
+                Some(lifetime) if is_elided_lifetime(lifetime) => env.diagnostics.push(explicit_lifetime_required),
+                Some(lifetime) => env.diagnostics.push(Diagnostic::error(String::from("lifetime may not live long enough"), loan.position,
+                    &format!("function was supposed to return data with lifetime `{}` but it is returning data with lifetime `{}` from `{}`",
+                        expected_lifetime, lifetime, name))),
+                None => env.diagnostics.push(explicit_lifetime_required),
+            }
+        } else if loan.place.num_of_derefs == 0 && !env.declared_statics_table.contains_key(&loan.place.variable) {
+            env.diagnostics.push(Diagnostic::error(format!("cannot return reference to local variable `{}`", name), loan.position,
+                "returns a reference to data owned by the current function")
+                .with_code("E0515"));
+        }
+    }
+}
+
 fn type_expression(expr : &  Expr, env : & mut Environment) -> DataType {
-    if is_identifier_expression(expr) {
-       unimplemented!();
-        // lookup_type(identifier(expr), env);
+    let (expr_type, _) = type_expression_with_loans(expr, env);
+    expr_type
+}
+
+// Also gives the loans held by the value of the expression, if it is a reference.
+fn type_expression_with_loans(expr : &  Expr, env : & mut Environment) -> (DataType, Loans) {
+    if is_error_expression(expr) {
+        (DataType::Unknown, vec![])// its syntax error is reported already.
+    } else if is_identifier_expression(expr) {
+        let own_type = lookup_type(identifier(expr), expr.get_span(), env);
+        let loans = update_scope_with_use(env, &resolve_name(identifier(expr), env), expr.get_span());
+        (own_type, loans)
     } else if is_literal(expr) {
         if is_integer_literal(literal(expr)) {
-            return DataType::Int64;
+            (DataType::Int64, vec![])
         } else if is_boolean_literal(literal(expr)) {
-            return DataType::Bool;
+            (DataType::Bool, vec![])
         } else if is_string_literal(literal(expr)) {
-            return DataType::String;
+            (DataType::Ref(Some(String::from("'static")), Box::new(DataType::Str)), vec![])
         } else if is_unit_literal(literal(expr)) {
-            return DataType::Unit;
+            (DataType::Unit, vec![])
         } else {
             panic!("unknown literal");
         }
     } else if is_block_expression(expr) {  //
-        let mut seq_copy = statements_of_block(block_of_expression(expr)).clone();
-        let (dt, hs) = type_and_handle_sequence(&mut seq_copy, env);
-        return dt;
+        let (dt, _, loans) = type_block(block_of_expression(expr), env);
+        (dt, loans)
     } else if is_primitive_operation_expression(expr) {
-        match expr {
-            Expr::PrimitiveOperationExpr(op, position) => DataType::Unit, // TODO //op.typecheck(env),
-            _ => panic!("")
-        }
+        primitive_operation(expr).typecheck(expr.get_span(), env)
+    } else if is_assignment_expression(expr) {
+        (type_assignment(expr, env), vec![])
+    } else if is_explicit_return(expr) {
+        (type_return(expr, env), vec![])
     } else if is_return_expression(expr) {
-        return type_expression(return_expression(expr), env); // MUST DO
+        let (return_type, loans) = type_expression_with_loans(return_expression(expr), env); // MUST DO
+        handle_stack(return_expression(expr), &return_type, env); // returned values move out of the block.
+        (return_type, loans)
+    } else if is_if_else_expression(expr) {
+        type_if_else(expr, env)
+    } else if is_loop_expression(expr) {
+        type_loop(expr, env)
+    } else if is_break_expression(expr) {
+        (type_break(expr, env), vec![])
+    } else if is_continue_expression(expr) {
+        let point = env.borrows.branch_point();
+        env.borrows.end_flow(point);
+        if let Some(properties) = find_enclosing_loop(continue_label(expr), expr.get_span(), "continue", env) {
+            let (head, scope_depth) = (properties.head, properties.scope_depth);
+            env.borrows.add_jump(point, head);
+            drop_scope_variables(scope_depth, expr.get_span(), env);
+        }
+        (DataType::Unit, vec![])
     } else if is_function_application_expression(expr) {
-        unimplemented!();
-        //type_application(function_name(expr), function_arguments(expr), env);
+        if is_println_application(expr) {
+            return (type_println(function_arguments(expr), expr.get_span(), env), vec![]);
+        } else if let Some(operator) = primitive_function_operator(expr) {
+            return type_primitive_application(operator, function_arguments(expr), expr.get_span(), env);
+        }
+        type_application(expr, env)
     } else {
-        panic!("Type Error at {:#?} for {:#?}", expr.get_source_location(), expr);
+        panic!("Type Error at {:#?} for {:#?}", expr.get_span(), expr);
     }
 }
 
-/*
-fn type_application(
-    function_name: & String,
-    function_arguments : & Vec<Expr>,
-     env: &mut Environment) -> DataType{
-         
+// The value is returned from the enclosing function, so control does not flow past a `return`.
+fn type_return(expr : & Expr, env : &mut Environment) -> DataType {
+    let value = return_expression(expr);
+    let (value_type, value_loans) = type_expression_with_loans(value, env);
+    handle_stack(value, &value_type, env); // returned values move out of the function.
 
+    let point = env.borrows.branch_point();
+    env.borrows.end_flow(point);
+    if let Some(properties) = env.enclosing_function.as_mut() {
+        properties.returns.push(point);
+        properties.return_loans.extend(value_loans);
+        let return_type = properties.return_type.clone();
+        // the scopes of the enclosing function are set aside, so every scope is left.
+        drop_scope_variables(0, expr.get_span(), env);
+        check_assignable(&value_type, &return_type, value.get_span(), env);
+    }
+    DataType::Never
+}
+
+// The temporaries of the value of a block are dropped at the end of the statement it is in.
+fn type_block(block : & Block, env : &mut Environment) -> (DataType, bool, Loans) {
+    let enclosing_temporaries = std::mem::take(&mut env.has_temporaries);
+    enter_scope(env);
+    let result = type_and_handle_sequence(statements_of_block(block), env);
+    exit_scope(block.get_span().end_point(), env);
+    env.has_temporaries |= enclosing_temporaries;
+    result
+}
+
+// The value of a sequence is that of its trailing return expression, or of its trailing block. A
+// sequence ending in a `return` is never left.
+fn type_and_handle_sequence(sequence : & Sequence, env : & mut Environment) -> (DataType, bool, Loans) {
+    // like top-level functions, functions declared within a sequence can be used anywhere in it.
+    for seq_stmt in sequence {
+        if is_sequence_statement(seq_stmt) && is_function_declaration(sequence_statement(seq_stmt)) {
+            declare_function(sequence_statement(seq_stmt), env);
+        }
+    }
+
+    let mut sequence_type = DataType::Unit;
+    let mut sequence_loans = vec![];
+    let mut has_return = false;
+    for (index, seq_stmt) in sequence.iter().enumerate() {
+        if has_return {
+            if let Some(position) = first_position_of_sequence(&sequence[index..]) {
+                env.diagnostics.push(Diagnostic::error(String::from("unreachable statement"), position, "unreachable statement")
+                    .with_note("any code following a `return` is unreachable"));
+            }
+            break;
+        }
+        if is_sequence_block(seq_stmt) {
+            let (block_type, block_has_return, block_loans) = type_block(sequence_block(seq_stmt), env);
+            sequence_type = block_type;
+            sequence_loans = block_loans;
+            has_return = block_has_return;
+            if !has_return {
+                drop_temporaries(seq_stmt.get_span(), env);
+            }
+            continue;
+        }
+
+        let stmt = sequence_statement(seq_stmt);
+        if is_expression_statement(stmt) && is_return_expression(expression_statement(stmt)) {
+            (sequence_type, sequence_loans) = type_expression_with_loans(expression_statement(stmt), env);
+            has_return = true;
+        } else if is_expression_statement(stmt) && is_error_expression(expression_statement(stmt)) {
+            // the value of a sequence ending in a syntax error is unknown, rather than a mismatch.
+            sequence_type = DataType::Unknown;
+            sequence_loans = vec![];
+        } else {
+            type_statement(stmt, env);
+            drop_temporaries(stmt.get_span(), env);
+            sequence_type = DataType::Unit;
+            sequence_loans = vec![];
+        }
+    }
+    (sequence_type, has_return, sequence_loans)
+}
+
+fn first_position_of_sequence(sequence : & [SequenceStmt]) -> Option<Span> {
+    for seq_stmt in sequence {
+        let position = match seq_stmt {
+            SequenceStmt::Stmt(stmt) => Some(stmt.get_span()),
+            SequenceStmt::Block(block) => first_position_of_sequence(statements_of_block(block)),
+        };
+        if position.is_some() {
+            return position;
+        }
+    }
+    None
+}
+
+// The value is evaluated before the target is written to. Assigning to a variable replaces the
+// loans it held with those of the new value, and re-initializes it if it has been moved out of.
+fn type_assignment(expr : & Expr, env : &mut Environment) -> DataType {
+    let value = assignment_value(expr);
+    let (value_type, value_loans) = type_expression_with_loans(value, env);
+    handle_stack(value, &value_type, env);
+
+    let target = assignee(expr);
+    let position = expr.get_span();
+    let target_type = match place_of(target, env) {
+        Some(place) if is_identifier_expression(target) => {
+            check_mutable_place(&place, true, position, env);
+            match env.variables_table.get_mut(&place.variable) {
+                Some(properties) => {
+                    if properties.own_type.is_none() {
+                        properties.is_copy_trait_mem = is_copy_type(&value_type);
+                        properties.own_type = Some(value_type.clone());
+                    }
+                    let target_type = properties.own_type.clone().unwrap();
+                    // the value it held before is dropped once the new one is computed.
+                    if needs_drop(&target_type) {
+                        insert_expired_lifetime(env, position, &place.variable);
+                    }
+                    env.borrows.record(BorrowEvent::Write(place.clone(), position));
+                    env.borrows.record(BorrowEvent::Hold(place.variable, value_loans));
+                    target_type
+                },
+                None => {
+                    let target_type = lookup_type(identifier(target), target.get_span(), env);
+                    env.borrows.record(BorrowEvent::Write(place, position));
+                    target_type
+                },
+            }
+        },
+        Some(place) => {
+            let (target_type, _) = type_place(target, env);
+            check_mutable_place(&place, true, position, env);
+            env.borrows.record(BorrowEvent::Write(place, position));
+            target_type
+        },
+        None => type_expression(target, env),
+    };
+    check_assignable(&value_type, &target_type, value.get_span(), env);
+    DataType::Unit
+}
+
+// The type of a place, without reading it. Going through a reference requires its loans, which
+// are held by the place as well.
+fn type_place(expr : & Expr, env : &mut Environment) -> (DataType, Loans) {
+    if is_identifier_expression(expr) {
+        let own_type = lookup_type(identifier(expr), expr.get_span(), env);
+        let binding = resolve_name(identifier(expr), env);
+        return (own_type, variable_loans(&binding, env));
+    }
+    let reference = unary_operand(primitive_operation(expr));
+    let (reference_type, loans) = type_place(reference, env);
+    env.borrows.record_use(&loans, expr.get_span());
+    (type_unary_operation(&UnaryOperator::Dereference, reference_type, expr.get_span(), env), loans)
+}
+
+// The loans handed to the function are needed until the call, and a returned reference may hold
+// on to any of them.
+fn type_application(expr : & Expr, env : &mut Environment) -> (DataType, Loans) {
+    let callee = function_callee(expr);
+    let position = expr.get_span();
+    let arguments = function_arguments(expr);
+    let (param_types, return_type) = match type_expression(callee, env) {
+        DataType::Func(_, param_types, return_type) => (param_types, return_type),
+        callee_type => {
+            if !is_unknown(&callee_type) {
+                env.diagnostics.push(Diagnostic::error(format!("expected function, found `{}`", callee_type), callee.get_span(),
+                    "call expression requires function")
+                    .with_code("E0618"));
+            }
+            for argument in arguments {
+                type_expression(argument, env);
+            }
+            return (DataType::Unknown, vec![]);
+        },
+    };
+
+    if arguments.len() != param_types.len() {
+        report_argument_count(param_types.len(), arguments.len(), position, env);
+    }
+    let mut loans = vec![];
+    for (argument, param_type) in arguments.iter().zip(param_types.iter()) {
+        let (argument_type, argument_loans) = type_expression_with_loans(argument, env);
+        check_assignable(&argument_type, param_type, argument.get_span(), env);
+        handle_stack(argument, &argument_type, env); // arguments are moved into the function.
+        loans.push(argument_loans);
+    }
+    env.borrows.record_use(&loans.concat(), callee.get_span());
+
+    // the returned reference holds on to the loans of the arguments whose lifetimes it names.
+    let return_lifetimes = lifetimes_of(&return_type);
+    let mut return_loans = vec![];
+    for (argument_loans, param_type) in loans.into_iter().zip(param_types.iter()) {
+        if lifetimes_of(param_type).iter().any(|lifetime| lifetime.is_some() && return_lifetimes.contains(lifetime))
+            || return_lifetimes.contains(&None) {
+            return_loans.extend(argument_loans);
+        }
+    }
+    (*return_type, return_loans)
+}
+
+fn type_primitive_application(operator : UnaryOperator, arguments : & [Expr], position : Span, env : &mut Environment) -> (DataType, Loans) {
+    let num_of_params = match operator {
+        UnaryOperator::PushStr => 2,
+        _ => 1,
+    };
+    if arguments.len() != num_of_params {
+        report_argument_count(num_of_params, arguments.len(), position, env);
+        for argument in arguments {
+            type_expression(argument, env);
+        }
+        return (type_unary_operation(&operator, DataType::Unknown, position, env), vec![]);
+    }
+
+    match operator {
+        UnaryOperator::PushStr => return (type_push_str(&arguments[0], &arguments[1], position, env), vec![]),
+        UnaryOperator::AsStr => return type_as_str(&arguments[0], position, env),
+        _ => (),
+    }
+
+    let operand_type = type_expression(&arguments[0], env);
+    if let UnaryOperator::Drop = operator {
+        handle_stack(&arguments[0], &operand_type, env); // dropped values are moved into drop.
+    }
+    handle_temporary(&arguments[0], &operand_type, env);
+    (type_unary_operation(&operator, operand_type, position, env), vec![])
+}
+
+fn report_argument_count(num_of_params : usize, num_of_args : usize, position : Span, env : &mut Environment) {
+    env.diagnostics.push(Diagnostic::error(format!("this function takes {} argument(s) but {} were supplied", num_of_params, num_of_args),
+        position, &format!("expected {} argument(s)", num_of_params))
+        .with_code("E0061"));
+}
+
+// Pushing onto a String place borrows it mutably for the duration of the call, while pushing through
+// a mutable reference writes to the String behind it.
+fn type_push_str(target : & Expr, string : & Expr, position : Span, env : &mut Environment) -> DataType {
+    let target_position = target.get_span();
+    let (target_type, target_loans) = match place_of(target, env) {
+        Some(place) => {
+            let (target_type, loans) = type_place(target, env);
+            match &target_type {
+                DataType::String => {
+                    check_mutable_place(&place, false, target_position, env);
+                    (target_type, vec![LoanSource::Loan(env.borrows.create_loan(place, BorrowKind::Mutable, target_position))])
+                },
+                DataType::MutRef(..) => {
+                    env.borrows.record_use(&loans, target_position);
+                    env.borrows.record(BorrowEvent::Write(Place { num_of_derefs : place.num_of_derefs + 1, ..place }, target_position));
+                    (target_type, loans)
+                },
+                DataType::Ref(..) => {
+                    check_mutable_place(&Place { num_of_derefs : place.num_of_derefs + 1, ..place }, false, target_position, env);
+                    (target_type, loans)
+                },
+                _ => (target_type, loans),
+            }
+        },
+        None => type_expression_with_loans(target, env),
+    };
+    match &target_type {
+        DataType::String => (),
+        DataType::MutRef(_, datatype) if is_same_type(datatype, &DataType::String) => (),
+        DataType::Ref(_, datatype) if is_same_type(datatype, &DataType::String) =>
+            // a place behind a `&` reference is reported as such.
+            if place_of(target, env).is_none() {
+                env.diagnostics.push(Diagnostic::error(String::from("cannot borrow data in a `&` reference as mutable"), target_position,
+                    "cannot borrow as mutable")
+                    .with_code("E0596"));
+            },
+        _ => report_mismatched_types(&target_type, &DataType::String, target_position, env),
+    }
+
+    let string_type = type_expression(string, env);
+    check_assignable(&string_type, &DataType::Ref(None, Box::new(DataType::Str)), string.get_span(), env);
+    env.borrows.record_use(&target_loans, position);
+    DataType::Unit
+}
+
+// Viewing a String place as a &str borrows it, while a reference to a String is reborrowed.
+fn type_as_str(operand : & Expr, position : Span, env : &mut Environment) -> (DataType, Loans) {
+    let (operand_type, loans) = match place_of(operand, env) {
+        Some(place) => {
+            let (operand_type, mut loans) = type_place(operand, env);
+            if is_mem_type(&operand_type) {
+                loans.insert(0, LoanSource::Loan(env.borrows.create_loan(place, BorrowKind::Shared, operand.get_span())));
+            } else {
+                env.borrows.record(BorrowEvent::Read(place, operand.get_span()));
+                env.borrows.record_use(&loans, operand.get_span());
+            }
+            (operand_type, loans)
+        },
+        None => type_expression_with_loans(operand, env),
+    };
+    (type_unary_operation(&UnaryOperator::AsStr, operand_type, position, env), loans)
+}
+
+
+fn type_println(arguments : &[Expr], position : Span, env : &mut Environment) -> DataType {
+    let format = match arguments.first() {
+        Some(Expr::LiteralExpr(Literal::StringLiteral(format), _)) => Some(format),
+        _ => {
+            env.diagnostics.push(Diagnostic::error(String::from("format argument must be a string literal"), position,
+                "println expects a format string literal as its first argument"));
+            None
+        },
+    };
+    match format.map(|format| format_string::parse(format)) {
+        Some(Ok(segments)) => {
+            let num_of_placeholders = format_string::count_placeholders(&segments);
+            let num_of_args = arguments.len() - 1;
+            if num_of_placeholders != num_of_args {
+                env.diagnostics.push(Diagnostic::error(format!("println has {} placeholder(s) but {} argument(s) were supplied", num_of_placeholders, num_of_args),
+                    position, ""));
+            }
+        },
+        Some(Err(message)) => env.diagnostics.push(Diagnostic::error(String::from("invalid format string"), position, "")
+            .with_note(&message)),
+        None => (),
+    }
+
+    for argument in arguments.iter().skip(1) {
+        let argument_type = type_expression(argument, env);
+        handle_temporary(argument, &argument_type, env);
+    }
+    DataType::Unit
+}
+
+fn type_if_else(expr : & Expr, env : &mut Environment) -> (DataType, Loans) {
+    let condition = if_else_condition(expr);
+    let condition_type = type_expression(condition, env);
+    if !is_assignable(&condition_type, &DataType::Bool) {
+        report_mismatched_types(&condition_type, &DataType::Bool, condition.get_span(), env);
+    }
+
+    // control flows into either branch, and out of both.
+    let branch = env.borrows.branch_point();
+    let (consequent_type, mut loans) = type_expression_with_loans(if_else_consequent(expr), env);
+    let consequent_end = env.borrows.branch_point();
+    env.borrows.end_flow(consequent_end);
+    env.borrows.add_jump(branch, env.borrows.next_point());
+
+    // a branch that is never left takes the type of the other.
+    let if_else_type = match if_else_alternative(expr) {
+        Some(alternative) => {
+            let (alternative_type, alternative_loans) = type_expression_with_loans(alternative, env);
+            loans.extend(alternative_loans);
+            if is_never(&consequent_type) {
+                alternative_type
+            } else {
+                if !is_never(&alternative_type) && !is_same_type(&consequent_type, &alternative_type) {
+                    env.diagnostics.push(Diagnostic::error(String::from("`if` and `else` have incompatible types"), alternative.get_span(),
+                        &format!("expected `{}`, found `{}`", consequent_type, alternative_type))
+                        .with_code("E0308")
+                        .with_label(if_else_consequent(expr).get_span(), "expected because of this"));
+                }
+                consequent_type
+            }
+        },
+        None => {
+            if !is_assignable(&consequent_type, &DataType::Unit) {
+                env.diagnostics.push(Diagnostic::error(String::from("`if` may be missing an `else` clause"), expr.get_span(),
+                    &format!("expected `()`, found `{}`", consequent_type))
+                    .with_code("E0317"));
+            }
+            DataType::Unit
+        },
+    };
+    let join = env.borrows.branch_point();
+    env.borrows.add_jump(consequent_end, join);
+    (if_else_type, loans)
+}
+
+fn type_loop(expr : & Expr, env : &mut Environment) -> (DataType, Loans) {
+    let is_while = is_while_expression(expr);
+    // the condition of a `while` loop is evaluated again on every iteration, and leaves the loop.
+    let head = env.borrows.branch_point();
+    let mut exits = vec![];
+    if is_while {
+        let condition = loop_condition(expr);
+        let condition_type = type_expression(condition, env);
+        if !is_assignable(&condition_type, &DataType::Bool) {
+            report_mismatched_types(&condition_type, &DataType::Bool, condition.get_span(), env);
+        }
+        exits.push(env.borrows.branch_point());
+    }
+
+    env.enclosing_loops.push_front(LoopProperties {
+        label : loop_label(expr).clone(),
+        break_type : None,
+        break_loans : vec![],
+        is_while,
+        scope_depth : env.scope_info.len(),
+        head,
+        exits,
+    });
+    type_expression(loop_body(expr), env);
+    let properties = env.enclosing_loops.pop_front().unwrap();
+
+    let back_edge = env.borrows.branch_point();
+    env.borrows.end_flow(back_edge);
+    env.borrows.add_jump(back_edge, head);
+    let exit = env.borrows.branch_point();
+    for point in &properties.exits {
+        env.borrows.add_jump(*point, exit);
+    }
+
+    // a `loop` without a `break` is only left by returning, if at all.
+    let loop_type = match properties.break_type {
+        Some(break_type) => break_type,
+        None if is_while => DataType::Unit,
+        None => DataType::Never,
+    };
+    (loop_type, properties.break_loans)
+}
+
+fn type_break(expr : & Expr, env : &mut Environment) -> DataType {
+    let position = expr.get_span();
+    let (value_type, value_loans) = match break_value(expr) {
+        Some(value) => {
+            let (value_type, value_loans) = type_expression_with_loans(value, env);
+            handle_stack(value, &value_type, env); // values broken with move out of the loop.
+            (value_type, value_loans)
+        },
+        None => (DataType::Unit, vec![]),
+    };
+
+    let point = env.borrows.branch_point();
+    env.borrows.end_flow(point);
+    let properties = match find_enclosing_loop(break_label(expr), position, "break", env) {
+        Some(properties) => properties,
+        None => return DataType::Unit,
+    };
+    properties.exits.push(point);
+    properties.break_loans.extend(value_loans);
+    let is_while = properties.is_while;
+    // a break with a value that is never given leaves the type to the other breaks.
+    let break_type = properties.break_type.clone().filter(|break_type| !is_never(break_type));
+    if break_type.is_none() && !is_while {
+        properties.break_type = Some(value_type.clone());
+    }
+    let scope_depth = properties.scope_depth;
+    drop_scope_variables(scope_depth, position, env);
+
+    if is_while && !is_assignable(&value_type, &DataType::Unit) {
+        env.diagnostics.push(Diagnostic::error(String::from("`break` with value from a `while` loop"), position,
+            "can only break with a value inside `loop`")
+            .with_code("E0571"));
+    } else if let Some(break_type) = break_type {
+        if !is_assignable(&value_type, &break_type) {
+            report_mismatched_types(&value_type, &break_type, position, env);
+        }
+    }
+    DataType::Unit
+}
+
+// Breaking out of a loop or continuing it leaves the scopes entered within it, as returning does
+// those of the function, whose variables are dropped at the given position.
+fn drop_scope_variables(scope_depth : usize, position : Span, env : &mut Environment) {
+    let num_of_scopes = env.scope_info.len() - scope_depth;
+    let names : Vec<String> = env.scope_info.iter()
+        .take(num_of_scopes)
+        .flat_map(|(declared_names, _)| declared_names.iter().rev())
+        .filter(|name| matches!(env.variables_table.get(*name), Some(VariableProperties { own_type : Some(own_type), .. }) if needs_drop(own_type)))
+        .cloned()
+        .collect();
+    for name in names {
+        insert_expired_lifetime(env, position, &name);
+    }
+}
+
+// The loop that is broken out of or continued, which is reported if there is none.
+fn find_enclosing_loop<'a>(label : & Option<String>, position : Span, keyword : &str, env : &'a mut Environment) -> Option<&'a mut LoopProperties> {
+    let index = match label {
+        Some(label) => env.enclosing_loops.iter().position(|properties| properties.label.as_ref() == Some(label)),
+        None if env.enclosing_loops.is_empty() => None,
+        None => Some(0),
+    };
+    if let Some(index) = index {
+        return env.enclosing_loops.iter_mut().nth(index);
+    }
+    let diagnostic = match label {
+        Some(label) => Diagnostic::error(format!("use of undeclared label `{}`", label), position, &format!("undeclared label `{}`", label))
+            .with_code("E0426"),
+        None => Diagnostic::error(format!("`{}` outside of a loop", keyword), position, &format!("cannot `{}` outside of a loop", keyword))
+            .with_code("E0268"),
+    };
+    env.diagnostics.push(diagnostic);
+    None
+}
+
+// Lifetime annotations are not part of the comparison.
+fn is_same_type(first : & DataType, second : & DataType) -> bool {
+    match (first, second) {
+        (DataType::Int64, DataType::Int64) | (DataType::Bool, DataType::Bool) | (DataType::Str, DataType::Str)
+        | (DataType::String, DataType::String) | (DataType::Unit, DataType::Unit) | (DataType::Never, DataType::Never) => true,
+        (DataType::Unknown, _) | (_, DataType::Unknown) => true,
+        (DataType::Ref(_, first), DataType::Ref(_, second))
+        | (DataType::MutRef(_, first), DataType::MutRef(_, second)) => is_same_type(first, second),
+        (DataType::Func(_, first_params, first_return), DataType::Func(_, second_params, second_return)) =>
+            first_params.len() == second_params.len()
+                && first_params.iter().zip(second_params.iter()).all(|(x, y)| is_same_type(x, y))
+                && is_same_type(first_return, second_return),
+        _ => false,
+    }
+}
+
+// Besides identical types, a mutable reference coerces to an immutable one, a reference to a
+// String coerces to a &str, and the never type coerces to any other.
+fn is_assignable(from : & DataType, to : & DataType) -> bool {
+    if is_same_type(from, to) {
+        return true;
+    }
+    match (from, to) {
+        (DataType::Never, _) => true,
+        (DataType::MutRef(_, from), DataType::Ref(_, to)) => is_assignable(from, to),
+        (DataType::Ref(_, from), DataType::Ref(_, to)) =>
+            is_same_type(from, &DataType::String) && is_same_type(to, &DataType::Str),
+        _ => false,
+    }
+}
+
+fn check_assignable(from : & DataType, to : & DataType, position : Span, env : &mut Environment) {
+    if !is_assignable(from, to) {
+        report_mismatched_types(from, to, position, env);
+    }
+}
+
+fn report_mismatched_types(found : & DataType, expected : & DataType, position : Span, env : &mut Environment) {
+    if is_unknown(found) || is_unknown(expected) {
+        return;
+    }
+    env.diagnostics.push(Diagnostic::error(String::from("mismatched types"), position, &format!("expected `{}`, found `{}`", expected, found))
+        .with_code("E0308"));
+}
+
+fn is_never(datatype : &DataType) -> bool {
+    matches!(datatype, DataType::Never)
+}
+
+fn is_unknown(datatype : &DataType) -> bool {
+    matches!(strip_reference(datatype), DataType::Unknown)
 }
-*/
 
 fn is_mem_type(datatype : &DataType) -> bool {
     match datatype{
@@ -169,149 +1311,201 @@ fn is_mem_type(datatype : &DataType) -> bool {
 
 fn is_copy_type(datatype : &DataType) -> bool {
     match datatype{
-        DataType::Int64 | DataType::Bool | DataType::Str => return true,
+        DataType::Int64 | DataType::Bool | DataType::Str | DataType::Unit | DataType::Func(..) => true,
+        DataType::Unknown | DataType::Never => true, // so that it is neither moved nor dropped.
         _ => return false,
     }
 }
 
-trait TypeCheck {
-    fn typecheck(&self, env: &mut Environment) -> DataType;
+// Only values that own heap memory have to be dropped.
+fn needs_drop(datatype : &DataType) -> bool {
+    is_mem_type(datatype) && !is_copy_type(datatype)
+}
+
+// Arithmetic is also implemented for references to its operands.
+fn strip_reference(datatype : &DataType) -> &DataType {
+    match datatype {
+        DataType::Ref(_, datatype) | DataType::MutRef(_, datatype) => datatype,
+        _ => datatype,
+    }
+}
+
+// String, &String and &str can be compared with one another.
+fn is_string_type(datatype : &DataType) -> bool {
+    matches!(strip_reference(datatype), DataType::String | DataType::Str)
 }
 
+trait TypeCheck {
+    fn typecheck(&self, position : Span, env: &mut Environment) -> (DataType, Loans);
+}
 
-/* parser incorrect
 impl TypeCheck for PrimitiveOperation {
-    fn typecheck(&self, env: &mut Environment) -> DataType {
+    fn typecheck(&self, position : Span, env: &mut Environment) -> (DataType, Loans) {
         match self {
             PrimitiveOperation::UnaryOperation { operator, operand } => match operator {
-                UnaryOperator::Not => match type_expression(operand, env) {
-                    DataType::Bool => return DataType::Bool,
-                    _ => panic!("Expect bool operand for not operation"),
+                UnaryOperator::ImmutableBorrow => type_borrow(BorrowKind::Shared, operand, position, env),
+                UnaryOperator::MutableBorrow => type_borrow(BorrowKind::Mutable, operand, position, env),
+                UnaryOperator::Dereference => type_dereference(self, position, env),
+                UnaryOperator::StringFrom | UnaryOperator::Len | UnaryOperator::AsStr
+                | UnaryOperator::Drop | UnaryOperator::PushStr =>
+                    type_primitive_application(*operator, std::slice::from_ref(operand), position, env),
+                _ => {
+                    let operand_type = type_expression(operand, env);
+                    (type_unary_operation(operator, operand_type, position, env), vec![])
                 },
-                UnaryOperator::UnaryMinus => match type_expression(operand, env) {
-                    DataType::Int64 => return  DataType::Int64,
-                    _ => panic!("Expect i64 for unary minus operation"),
-                },
-                UnaryOperator::ImmutableBorrow => match type_expression(operand, env) {
-                    DataType::Func(..) => panic!("References to functions are currently unsupported"),
-                    datatype@_ => return DataType::Ref(None, Box::new(datatype))
-                },
-                UnaryOperator::MutableBorrow => match type_expression(operand, env) {
-                    DataType::Func(..) => panic!("Mutable references to functions are currently unsupported"),
-                    datatype@_ => return DataType::MutRef(None, Box::new(datatype))
-                },
-                UnaryOperator::Dereference => match type_expression(operand, env) {
-                    DataType::Ref(_, dereferenced_type) => return *dereferenced_type,
-                    DataType::MutRef(_, dereferenced_type) => return *dereferenced_type,
-                    _ => panic!("Dereferencing can only be performed on a mutable or immutable borrow"),
-                },
-                UnaryOperator::StringFrom => match type_expression(operand, env) {
-                    DataType::Ref(_, boxed_type) => match *boxed_type {
-                        DataType::Str => return DataType::String,
-                        _ => panic!("Expect &str for string_from operation"),
-                    },
-                    _ => panic!("Expect &str for string_from operation"),
-                },
-                UnaryOperator::Len => match type_expression(operand, env) {
-                    DataType::Ref(_, boxed) => match *boxed {
-                        DataType::Str => return DataType::Int64,
-                        _ => panic!("none found in the boxed"),
-                    }
-                    DataType::String => DataType::Int64,
-                    _ => panic!("Expect &str or String for len operation"),
-                },
-                UnaryOperator::AsStr => match type_expression(operand, env) {
-                    
-                },,
-                UnaryOperator::PushStr => panic!("parser mistaken. Push_str not supported."),
-                UnaryOperator::Drop => unimplemented!(),
             },
             PrimitiveOperation::BinaryOperation { operator, first_operand, second_operand } => {
-                match operator {
-                    BinaryOperator::Plus => vec![Instruction::PLUS],
-                    BinaryOperator::Minus => vec![Instruction::MINUS],
-                    BinaryOperator::Times => vec![Instruction::TIMES],
-                    BinaryOperator::Divide => vec![Instruction::DIV],
-                    BinaryOperator::Equal => vec![Instruction::EQUAL],
-                    BinaryOperator::NotEqual => vec![Instruction::EQUAL, Instruction::NOT],
-                    BinaryOperator::Greater => vec![Instruction::GREATER],
-                    BinaryOperator::GreaterOrEqual => vec![Instruction::GEQ],
-                    BinaryOperator::Less => vec![Instruction::LESS],
-                    BinaryOperator::LessOrEqual => vec![Instruction::LEQ],
-                    BinaryOperator::And => vec![Instruction::AND],
-                    BinaryOperator::Or => vec![Instruction::OR],
-                }
-            }
-            PrimitiveOperation::VariadicOperation { operator, operands } => 
-                match type_check(operands, env) {
-                    VariadicOperator::Println => unimplemented!(),
-                }
+                let first_type = type_expression(first_operand, env);
+                handle_temporary(first_operand, &first_type, env);
+                let second_type = type_expression(second_operand, env);
+                handle_temporary(second_operand, &second_type, env);
+                (type_binary_operation(operator, &first_type, &second_type, position, env), vec![])
+            },
+            PrimitiveOperation::VariadicOperation { operator, operands } => match operator {
+                VariadicOperator::Println => (type_println(operands, position, env), vec![]),
+            },
         }
     }
 }
-*/
 
+// Borrowing a place creates a loan on it. The reference also holds on to the loans of the place,
+// so that e.g. the reborrow `&mut *y` keeps the loans of `y` alive.
+fn type_borrow(kind : BorrowKind, operand : & Expr, position : Span, env : &mut Environment) -> (DataType, Loans) {
+    let (operand_type, loans) = match place_of(operand, env) {
+        Some(place) => {
+            let (operand_type, mut loans) = type_place(operand, env);
+            if kind == BorrowKind::Mutable {
+                check_mutable_place(&place, false, position, env);
+            }
+            loans.insert(0, LoanSource::Loan(env.borrows.create_loan(place, kind, position)));
+            (operand_type, loans)
+        },
+        None => type_expression_with_loans(operand, env), // borrows of temporaries cannot conflict.
+    };
+    let operator = match kind {
+        BorrowKind::Shared => UnaryOperator::ImmutableBorrow,
+        BorrowKind::Mutable => UnaryOperator::MutableBorrow,
+    };
+    (type_unary_operation(&operator, operand_type, position, env), loans)
+}
 
-//MUST HAVE RETURN STATEMENT SOMEWHERE. OTHERWISE RETURN NONE.
-fn type_and_handle_sequence(sequence : &mut Sequence, env : & mut Environment) -> (DataType, bool) { // must have return o
-    if is_empty_sequence(sequence) {
-        return (DataType::Unit, false);
-        //SB here
-    } else if is_last_statement_of_sequence(sequence) { // MUST BE RETURN. OTHERWISE NONE. HANDLE HERE.
-        match first_statement_of_sequence(sequence) {
-            SequenceStmt::Stmt(stmt) => 
-                match stmt {
-                    Stmt::ExprStmt(expr) => 
-                     if is_return_expression(expr) {
-                         return (type_expression(expr, env), true);   
-                     } else {
-                         let curr_stmt_type = type_expression(expr, env);
-                         return (DataType::Unit, false);
-                     },
-                    _ => {
-                        let curr_stmt_type = type_statement(stmt, env);
-                        return (DataType::Unit, false);
-                    },
-                },
-            SequenceStmt::Block(block) => {
-                let mut mut_seq = statements_of_block(block).clone();
-                return type_and_handle_sequence(&mut mut_seq, env);
+// Reading through a reference. The value read only holds on to the loans if it is a reference too.
+fn type_dereference(operation : & PrimitiveOperation, position : Span, env : &mut Environment) -> (DataType, Loans) {
+    let operand = unary_operand(operation);
+    let (dereferenced_type, loans) = match place_of(operand, env) {
+        Some(place) => {
+            let (operand_type, loans) = type_place(operand, env);
+            env.borrows.record_use(&loans, position);
+            let place = Place { num_of_derefs : place.num_of_derefs + 1, ..place };
+            env.borrows.record(BorrowEvent::Read(place, position));
+            (type_unary_operation(&UnaryOperator::Dereference, operand_type, position, env), loans)
+        },
+        None => {
+            let (operand_type, loans) = type_expression_with_loans(operand, env);
+            (type_unary_operation(&UnaryOperator::Dereference, operand_type, position, env), loans)
+        },
+    };
+    if is_mem_type(&dereferenced_type) {
+        return (dereferenced_type, vec![]);
+    }
+    (dereferenced_type, loans)
+}
+
+// The types of the unary primitive operations, as given by Prim1T. An operand of the wrong type is
+// reported, and the operation is given the type it has otherwise.
+fn type_unary_operation(operator : & UnaryOperator, operand_type : DataType, position : Span, env : &mut Environment) -> DataType {
+    let (operation_type, is_valid) = match operator {
+        UnaryOperator::Not => (DataType::Bool, is_assignable(strip_reference(&operand_type), &DataType::Bool)),
+        UnaryOperator::UnaryMinus => (DataType::Int64, is_assignable(strip_reference(&operand_type), &DataType::Int64)),
+        UnaryOperator::ImmutableBorrow => match &operand_type {
+            DataType::Func(..) => {
+                report_unsupported("references to functions are currently unsupported", position, env);
+                (DataType::Ref(None, Box::new(operand_type.clone())), true)
             },
-        }
-    } else { // IF RETURN, PANIC IF EARLY END.
-        match first_statement_of_sequence(sequence) {
-            SequenceStmt::Stmt(stmt) => 
-                match stmt {
-                    Stmt::ExprStmt(expr) => 
-                    panic! ("Unreacheable statement not allowed at {:#?} for {:#?}", stmt.get_source_location(), expr),
-                    _ => {let curr_stmt_type = type_statement(stmt, env);
-                          return type_and_handle_sequence(rest_statements_of_sequence(sequence), env)},
-                },
-            SequenceStmt::Block(block) => {
-                let mut mut_seq = statements_of_block(block).clone();
-                let (datatype, has_return) = type_and_handle_sequence(&mut mut_seq, env);
-                if has_return {
-                    panic! ("Unreacheable statement not allowed for {:#?}", block);
-                }
-                return type_and_handle_sequence(rest_statements_of_sequence(sequence), env);
+            _ => (DataType::Ref(None, Box::new(operand_type.clone())), true),
+        },
+        UnaryOperator::MutableBorrow => match &operand_type {
+            DataType::Func(..) => {
+                report_unsupported("mutable references to functions are currently unsupported", position, env);
+                (DataType::MutRef(None, Box::new(operand_type.clone())), true)
             },
-        }
+            _ => (DataType::MutRef(None, Box::new(operand_type.clone())), true),
+        },
+        UnaryOperator::Dereference => match &operand_type {
+            DataType::Ref(_, dereferenced_type) | DataType::MutRef(_, dereferenced_type) => return *dereferenced_type.clone(),
+            _ => (DataType::Unknown, is_unknown(&operand_type)),
+        },
+        UnaryOperator::StringFrom => {
+            check_assignable(&operand_type, &DataType::Ref(None, Box::new(DataType::Str)), position, env);
+            (DataType::String, true)
+        },
+        UnaryOperator::Len => (DataType::Int64, is_string_type(&operand_type) || is_unknown(&operand_type)),
+        UnaryOperator::AsStr => (DataType::Ref(None, Box::new(DataType::Str)), is_same_type(strip_reference(&operand_type), &DataType::String)),
+        UnaryOperator::Drop => (DataType::Unit, true),
+        // only reached once the missing string to push is reported.
+        UnaryOperator::PushStr => (DataType::Unit, true),
+    };
+    if is_valid {
+        return operation_type;
     }
+    let diagnostic = match operator {
+        UnaryOperator::Not | UnaryOperator::UnaryMinus => {
+            let operator_name = if let UnaryOperator::Not = operator { "!" } else { "-" };
+            Diagnostic::error(format!("cannot apply unary operator `{}` to type `{}`", operator_name, operand_type), position,
+                &format!("cannot apply unary operator `{}`", operator_name))
+                .with_code("E0600")
+        },
+        UnaryOperator::Dereference => Diagnostic::error(format!("type `{}` cannot be dereferenced", operand_type), position, "")
+            .with_code("E0614"),
+        UnaryOperator::Len => Diagnostic::error(format!("expected `&str` or `String` for `len`, found `{}`", operand_type), position, ""),
+        _ => Diagnostic::error(format!("expected `String` for `as_str`, found `{}`", operand_type), position, ""),
+    };
+    env.diagnostics.push(diagnostic);
+    operation_type
 }
 
-fn is_empty_sequence(sequence : &mut Sequence) -> bool {
-    return sequence.is_empty();
-}
-fn is_last_statement_of_sequence(sequence : &mut Sequence) -> bool {
-    return sequence.len() == 1;
+fn report_unsupported(message : &str, position : Span, env : &mut Environment) {
+    env.diagnostics.push(Diagnostic::error(String::from(message), position, ""));
 }
-fn first_statement_of_sequence(sequence : &mut Sequence) -> & SequenceStmt {
-    return & sequence[0];
+
+// The types of the binary primitive operations, as given by Prim2T. Operands of the wrong types are
+// reported, and the operation is given the type it has otherwise.
+fn type_binary_operation(operator : & BinaryOperator, first_type : & DataType, second_type : & DataType, position : Span, env : &mut Environment) -> DataType {
+    let (operation_type, is_valid) = match operator {
+        BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Times
+        | BinaryOperator::Divide | BinaryOperator::Modulo =>
+            (DataType::Int64, is_assignable(strip_reference(first_type), &DataType::Int64) && is_assignable(strip_reference(second_type), &DataType::Int64)),
+        BinaryOperator::Greater | BinaryOperator::GreaterOrEqual | BinaryOperator::Less | BinaryOperator::LessOrEqual =>
+            (DataType::Bool, is_same_type(first_type, second_type) && is_same_type(strip_reference(first_type), &DataType::Int64)),
+        BinaryOperator::Equal | BinaryOperator::NotEqual =>
+            (DataType::Bool, is_same_type(first_type, second_type) || (is_string_type(first_type) && is_string_type(second_type))),
+        BinaryOperator::And | BinaryOperator::Or =>
+            (DataType::Bool, is_assignable(first_type, &DataType::Bool) && is_assignable(second_type, &DataType::Bool)),
+    };
+    if !is_valid {
+        env.diagnostics.push(Diagnostic::error(format!("cannot apply binary operator `{}` to types `{}` and `{}`",
+            binary_operator_name(operator), first_type, second_type), position, "")
+            .with_code("E0369"));
+    }
+    operation_type
 }
-fn rest_statements_of_sequence(sequence : &mut Sequence) -> &mut Sequence {
-    let lastIdx = sequence.len() - 1;
-    sequence.remove(0);
-    return sequence;
+
+fn binary_operator_name(operator : & BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::Plus => "+",
+        BinaryOperator::Minus => "-",
+        BinaryOperator::Times => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Equal => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::GreaterOrEqual => ">=",
+        BinaryOperator::Less => "<",
+        BinaryOperator::LessOrEqual => "<=",
+        BinaryOperator::And => "&&",
+        BinaryOperator::Or => "||",
+    }
 }
 
 
@@ -348,10 +1542,10 @@ fn let_statement_value(stmt : & Stmt) -> & Option<Expr> {
         _ => panic!("No let statement in call for its value: {:#?}", stmt),
     }
 }
-fn non_optional_value(non_optional_let_value : & Option<Expr>) -> & Expr {// assume not optional
-    match non_optional_let_value {
-        Some(val) => val,
-        None => panic!("No right hand side in let statement"),
+fn let_statement_name_position(stmt : & Stmt) -> Span {
+    match stmt {
+        Stmt::LetStmt { name, ..} => name.get_span(),
+        _ => panic!("No let statement in call for its name: {:#?}", stmt),
     }
 }
 fn is_mutable_let_statement(stmt : & Stmt) -> bool { 
@@ -372,6 +1566,12 @@ fn is_function_declaration(stmt : & Stmt) -> bool {
         _ => return false
     }
 }
+fn function_declaration_name_position(stmt : & Stmt) -> Span {
+    match stmt {
+        Stmt::FuncDeclaration { name, ..} => name.get_span(),
+        _ => panic!("No function declaration in call for its name: {:#?}", stmt),
+    }
+}
 fn function_declaration_name(stmt : & Stmt) -> & String {
     match stmt {
         Stmt::FuncDeclaration { name, ..}
@@ -382,6 +1582,13 @@ fn function_declaration_name(stmt : & Stmt) -> & String {
         _ => panic!("No function declaration in call for its name: {:#?}", stmt),
     }
 }
+fn function_declaration_lifetime_parameters(stmt : & Stmt) -> & Vec<LifetimeParameter> {
+    match stmt {
+        Stmt::FuncDeclaration { lifetime_parameters, ..}
+         => lifetime_parameters,
+        _ => panic!("No function declaration in call for its lifetime parameters : {:#?}", stmt)
+    }
+}
 fn function_declaration_parameters(stmt : & Stmt) -> & Vec<FuncParameter> {
     match stmt {
         Stmt::FuncDeclaration { parameters, ..}
@@ -389,10 +1596,10 @@ fn function_declaration_parameters(stmt : & Stmt) -> & Vec<FuncParameter> {
         _ => panic!("No function declaration in call for its parameters : {:#?}", stmt)
     }
 }
-fn function_declaration_body(stmt : & Stmt) -> & Sequence {
+fn function_declaration_body(stmt : & Stmt) -> & Block {
     match stmt {
         Stmt::FuncDeclaration { body, ..}
-         => return & body.statements,
+         => body,
          _ => panic!("No function declaration in call for its parameters : {:#?}", stmt)
     }
 }
@@ -403,7 +1610,48 @@ fn function_declaration_return_type(stmt : & Stmt) -> & DataType {
          _ => panic!("No function declaration in call for its return type : {:#?}", stmt)
     }
 }
-// assume no static statements
+
+/*STATIC STATEMENTS*/
+fn is_static_statement(stmt : & Stmt) -> bool {
+    matches!(stmt, Stmt::StaticStmt {..})
+}
+fn static_statement_name_position(stmt : & Stmt) -> Span {
+    match stmt {
+        Stmt::StaticStmt { name, ..} => name.get_span(),
+        _ => panic!("No static statement in call for its name: {:#?}", stmt),
+    }
+}
+fn static_statement_name(stmt : & Stmt) -> & String {
+    match stmt {
+        Stmt::StaticStmt { name, ..}
+         => match name {
+             Expr::IdentifierExpr(name_string, _) => name_string,
+             _ => panic!("Name of static statement is not an identifier expression. {:#?}", name),
+         }
+        _ => panic!("No static statement in call for its name: {:#?}", stmt),
+    }
+}
+fn static_statement_type(stmt : & Stmt) -> & DataType {
+    match stmt {
+        Stmt::StaticStmt { annotation, ..}
+         => annotation,
+        _ => panic!("No static statement in call for its type: {:#?}", stmt),
+    }
+}
+fn static_statement_value(stmt : & Stmt) -> & Expr {
+    match stmt {
+        Stmt::StaticStmt { value, ..}
+         => value,
+        _ => panic!("No static statement in call for its value: {:#?}", stmt),
+    }
+}
+fn is_mutable_static_statement(stmt : & Stmt) -> bool {
+    match stmt {
+        Stmt::StaticStmt {is_mutable, ..}
+         => *is_mutable,
+         _ => panic!("No static statement in call for its mutability: {:#?}", stmt),
+    }
+}
 
 /* EXPRESSION STATEMENTS*/
 //ExprStmt is a parent name.
@@ -420,6 +1668,10 @@ fn expression_statement(stmt : & Stmt) -> & Expr { // either a uses or modifies
         _ => panic!("No expression statement in call for its expression: {:#?}", stmt)
     }
 }
+//ERROR
+fn is_error_expression(expr : &Expr) -> bool { // source that failed to parse
+    matches!(expr, Expr::ErrorExpr(..))
+}
 //IDENTIFIER
 fn is_identifier_expression(expr : &Expr) -> bool { // uses
     match expr {
@@ -501,11 +1753,7 @@ fn block_of_expression(expr : & Expr) -> & Block {
     }
 }
 fn statements_of_block(block : & Block) -> & Sequence {
-    match block {
-        Block{statements}
-            => return statements,
-            _ => panic!("Block expression is not present at call to sequence. {:#?}", block),
-    } 
+    &block.statements
 }
 //OPERATIONS
 fn is_primitive_operation_expression(expr : & Expr) -> bool{
@@ -606,6 +1854,22 @@ fn function_name(expr : & Expr) -> & String {   // returns the string ptr name.
         _ => panic!("Not a function application {:#?}", expr),
     }
 } 
+fn function_callee(expr : & Expr) -> & Expr {
+    match expr {
+        Expr::ApplicationExpr{callee, ..} => callee,
+        _ => panic!("Not a function application {:#?}", expr),
+    }
+}
+fn primitive_function_operator(expr : & Expr) -> Option<UnaryOperator> {
+    match expr {
+        Expr::ApplicationExpr{is_primitive: Some(PrimitiveOperator::Unary(operator)), ..}
+        => Some(*operator),
+        _ => None,
+    }
+}
+fn is_println_application(expr : & Expr) -> bool {
+    matches!(expr, Expr::ApplicationExpr{is_primitive: Some(PrimitiveOperator::VariadicOperator(VariadicOperator::Println)), ..})
+}
 fn function_arguments(expr : & Expr) -> & Vec<Expr> {   // must check the kind of expr of arguments.
     match expr {
         Expr::ApplicationExpr{arguments, ..}
@@ -628,6 +1892,77 @@ fn return_expression(expr : & Expr) -> & Expr {
         _ => panic!("Not a return expression {:#?}", expr),
     }
 }
+// IF ELSE
+fn is_if_else_expression(expr : & Expr) -> bool {
+    matches!(expr, Expr::IfElseExpr{..})
+}
+fn if_else_condition(expr : & Expr) -> & Expr {
+    match expr {
+        Expr::IfElseExpr{condition, ..} => condition,
+        _ => panic!("Not an if else expression {:#?}", expr),
+    }
+}
+fn if_else_consequent(expr : & Expr) -> & Expr {
+    match expr {
+        Expr::IfElseExpr{consequent, ..} => consequent,
+        _ => panic!("Not an if else expression {:#?}", expr),
+    }
+}
+fn if_else_alternative(expr : & Expr) -> Option<& Expr> {
+    match expr {
+        Expr::IfElseExpr{alternative, ..} => alternative.as_deref(),
+        _ => panic!("Not an if else expression {:#?}", expr),
+    }
+}
+// LOOPS
+fn is_loop_expression(expr : & Expr) -> bool {
+    matches!(expr, Expr::LoopExpr{..} | Expr::WhileExpr{..})
+}
+fn is_while_expression(expr : & Expr) -> bool {
+    matches!(expr, Expr::WhileExpr{..})
+}
+fn loop_label(expr : & Expr) -> & Option<String> {
+    match expr {
+        Expr::LoopExpr{label, ..} | Expr::WhileExpr{label, ..} => label,
+        _ => panic!("Not a loop expression {:#?}", expr),
+    }
+}
+fn loop_condition(expr : & Expr) -> & Expr {
+    match expr {
+        Expr::WhileExpr{condition, ..} => condition,
+        _ => panic!("Not a while expression {:#?}", expr),
+    }
+}
+fn loop_body(expr : & Expr) -> & Expr {
+    match expr {
+        Expr::LoopExpr{body, ..} | Expr::WhileExpr{body, ..} => body,
+        _ => panic!("Not a loop expression {:#?}", expr),
+    }
+}
+fn is_break_expression(expr : & Expr) -> bool {
+    matches!(expr, Expr::BreakExpr{..})
+}
+fn break_label(expr : & Expr) -> & Option<String> {
+    match expr {
+        Expr::BreakExpr{label, ..} => label,
+        _ => panic!("Not a break expression {:#?}", expr),
+    }
+}
+fn break_value(expr : & Expr) -> Option<& Expr> {
+    match expr {
+        Expr::BreakExpr{value, ..} => value.as_deref(),
+        _ => panic!("Not a break expression {:#?}", expr),
+    }
+}
+fn is_continue_expression(expr : & Expr) -> bool {
+    matches!(expr, Expr::ContinueExpr{..})
+}
+fn continue_label(expr : & Expr) -> & Option<String> {
+    match expr {
+        Expr::ContinueExpr{label, ..} => label,
+        _ => panic!("Not a continue expression {:#?}", expr),
+    }
+}
 /* SEQUENCE and SEQUENCE STATEMENTS*/
 fn is_sequence_statement(sequence_stmt : & SequenceStmt) -> bool {
     match sequence_stmt {
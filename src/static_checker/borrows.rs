@@ -0,0 +1,358 @@
+use crate::parser::ast::{Span, DataType, LifetimeParameter, source_name};
+use crate::diagnostic::Diagnostic;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+// The loans of a function body are recorded as a control flow graph of borrow events, and only
+// checked once the whole body has been seen. A loan is live at a point if a reference holding on
+// to it may be used later on, before being assigned another value.
+
+// The value of an expression holds on to the loans it creates, and to those held by the variables
+// it is computed from, which are only known once the flow of the function has been analysed.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum LoanSource {
+    Loan(usize),
+    Variable(String),
+}
+
+pub type Loans = Vec<LoanSource>;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BorrowKind {
+    Shared,
+    Mutable,
+}
+
+// A variable, known by its binding, dereferenced the given number of times.
+#[derive(Clone, PartialEq)]
+pub struct Place {
+    pub variable : String,
+    pub num_of_derefs : usize,
+}
+
+impl Display for Place {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", "*".repeat(self.num_of_derefs), source_name(&self.variable))
+    }
+}
+
+pub struct Loan {
+    pub place : Place,
+    pub kind : BorrowKind,
+    pub position : Span,
+    // the loans of the caller on the data behind a reference parameter are only known by the
+    // lifetime of the parameter.
+    pub is_parameter : bool,
+    pub lifetime : Option<LifetimeParameter>,
+}
+
+pub enum BorrowEvent {
+    Borrow(usize),                      // creates the loan with the given index.
+    Use(Loans, Span),         // requires the loans to still be live.
+    Hold(String, Loans),                // the variable is given a value holding on to the loans.
+    Read(Place, Span),
+    Write(Place, Span),
+    Move(Place, Span),
+    StorageLive(String, bool, Span), // the variable, mutable or not, is declared without a value.
+    StorageDead(String, Span), // the variable goes out of scope.
+    Branch,                             // a point where control flow splits or joins.
+}
+
+#[derive(Default)]
+pub struct FunctionBorrows {
+    pub loans : Vec<Loan>,
+    pub events : Vec<BorrowEvent>,
+    // control flows from each event to the next one, except after the given events, and along
+    // the given jumps.
+    jumps : Vec<(usize, usize)>,
+    dead_ends : HashSet<usize>,
+    // the type of each variable of the function, and where it is declared.
+    pub declarations : HashMap<String, (DataType, Span)>,
+}
+
+impl FunctionBorrows {
+    pub fn create_loan(&mut self, place : Place, kind : BorrowKind, position : Span) -> usize {
+        let index = self.loans.len();
+        self.loans.push(Loan { place, kind, position, is_parameter : false, lifetime : None });
+        self.events.push(BorrowEvent::Borrow(index));
+        index
+    }
+
+    pub fn create_parameter_loan(&mut self, place : Place, position : Span, lifetime : Option<LifetimeParameter>) -> usize {
+        let index = self.loans.len();
+        self.loans.push(Loan { place, kind : BorrowKind::Shared, position, is_parameter : true, lifetime });
+        index
+    }
+
+    pub fn record_use(&mut self, loans : & Loans, position : Span) {
+        if !loans.is_empty() {
+            self.events.push(BorrowEvent::Use(loans.clone(), position));
+        }
+    }
+
+    pub fn record(&mut self, event : BorrowEvent) {
+        self.events.push(event);
+    }
+
+    // Adds a point for control flow to split from or join at.
+    pub fn branch_point(&mut self) -> usize {
+        self.events.push(BorrowEvent::Branch);
+        self.events.len() - 1
+    }
+
+    // The index of the next event to be recorded.
+    pub fn next_point(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn add_jump(&mut self, from : usize, to : usize) {
+        self.jumps.push((from, to));
+    }
+
+    // Control does not flow from the given point to the next one.
+    pub fn end_flow(&mut self, point : usize) {
+        self.dead_ends.insert(point);
+    }
+
+    pub fn successors(&self) -> Vec<Vec<usize>> {
+        let num_of_points = self.events.len();
+        let mut successors : Vec<Vec<usize>> = (0..num_of_points)
+            .map(|point| if point + 1 < num_of_points && !self.dead_ends.contains(&point) { vec![point + 1] } else { vec![] })
+            .collect();
+        for &(from, to) in &self.jumps {
+            if to < num_of_points {
+                successors[from].push(to);
+            }
+        }
+        successors
+    }
+}
+
+type HeldLoans = HashMap<String, HashSet<usize>>;
+
+// The loans each variable may hold before every point, and the holders whose values may be used
+// after every point. Holders are variables, and loans not yet given to a variable.
+pub struct FlowAnalysis {
+    successors : Vec<Vec<usize>>,
+    held_before : Vec<HeldLoans>,
+    held_after : Vec<HeldLoans>,
+    live_after : Vec<HashSet<LoanSource>>,
+}
+
+pub fn analyse(borrows : & FunctionBorrows) -> FlowAnalysis {
+    let successors = borrows.successors();
+    let num_of_points = borrows.events.len();
+    let mut predecessors : Vec<Vec<usize>> = vec![vec![]; num_of_points];
+    for (point, point_successors) in successors.iter().enumerate() {
+        for &successor in point_successors {
+            predecessors[successor].push(point);
+        }
+    }
+
+    // forwards, the loans held by each variable.
+    let mut held_before : Vec<HeldLoans> = vec![HashMap::new(); num_of_points];
+    let mut held_after : Vec<HeldLoans> = vec![HashMap::new(); num_of_points];
+    let mut is_changed = true;
+    while is_changed {
+        is_changed = false;
+        for point in 0..num_of_points {
+            let mut held = HeldLoans::new();
+            for &predecessor in &predecessors[point] {
+                for (variable, loans) in &held_after[predecessor] {
+                    held.entry(variable.clone()).or_default().extend(loans);
+                }
+            }
+            let mut held_at_end = held.clone();
+            if let BorrowEvent::Hold(variable, sources) = &borrows.events[point] {
+                held_at_end.insert(variable.clone(), resolve_loans(sources, &held));
+            }
+            if held_at_end != held_after[point] {
+                held_after[point] = held_at_end;
+                is_changed = true;
+            }
+            held_before[point] = held;
+        }
+    }
+
+    // backwards, the holders that are used later on.
+    let mut live_after : Vec<HashSet<LoanSource>> = vec![HashSet::new(); num_of_points];
+    let mut live_before : Vec<HashSet<LoanSource>> = vec![HashSet::new(); num_of_points];
+    is_changed = true;
+    while is_changed {
+        is_changed = false;
+        for point in (0..num_of_points).rev() {
+            let mut live : HashSet<LoanSource> = HashSet::new();
+            for &successor in &successors[point] {
+                live.extend(live_before[successor].iter().cloned());
+            }
+            let mut live_at_start = live.clone();
+            match &borrows.events[point] {
+                BorrowEvent::Borrow(index) => { live_at_start.remove(&LoanSource::Loan(*index)); },
+                BorrowEvent::Hold(variable, _) => { live_at_start.remove(&LoanSource::Variable(variable.clone())); },
+                BorrowEvent::Use(sources, _) => live_at_start.extend(sources.iter().cloned()),
+                _ => (),
+            }
+            if live_at_start != live_before[point] {
+                live_before[point] = live_at_start;
+                is_changed = true;
+            }
+            live_after[point] = live;
+        }
+    }
+
+    FlowAnalysis { successors, held_before, held_after, live_after }
+}
+
+fn resolve_loans(sources : & Loans, held : & HeldLoans) -> HashSet<usize> {
+    let mut loans = HashSet::new();
+    for source in sources {
+        match source {
+            LoanSource::Loan(index) => { loans.insert(*index); },
+            LoanSource::Variable(variable) => if let Some(held_loans) = held.get(variable) {
+                loans.extend(held_loans);
+            },
+        }
+    }
+    loans
+}
+
+impl FlowAnalysis {
+    // The loans of a value computed at the end of the function.
+    pub fn loans_at_exit(&self, sources : & Loans) -> Vec<usize> {
+        let mut loans : Vec<usize> = match self.held_after.last() {
+            Some(held) => resolve_loans(sources, held).into_iter().collect(),
+            None => resolve_loans(sources, &HeldLoans::new()).into_iter().collect(),
+        };
+        loans.sort();
+        loans
+    }
+
+    fn is_live_after(&self, loan : usize, point : usize) -> bool {
+        self.live_after[point].iter().any(|holder| match holder {
+            LoanSource::Loan(index) => *index == loan,
+            LoanSource::Variable(variable) => self.held_after[point].get(variable).is_some_and(|loans| loans.contains(&loan)),
+        })
+    }
+
+    // The first use of the loan reachable from the point, to report along with a conflict.
+    fn next_use(&self, borrows : & FunctionBorrows, loan : usize, point : usize) -> Option<Span> {
+        let mut is_visited = vec![false; self.successors.len()];
+        let mut frontier : Vec<usize> = self.successors[point].clone();
+        let mut index = 0;
+        while index < frontier.len() {
+            let current = frontier[index];
+            index += 1;
+            if is_visited[current] {
+                continue;
+            }
+            is_visited[current] = true;
+            if let BorrowEvent::Use(sources, position) = &borrows.events[current] {
+                if resolve_loans(sources, &self.held_before[current]).contains(&loan) {
+                    return Some(*position);
+                }
+            }
+            frontier.extend(self.successors[current].iter().copied());
+        }
+        None
+    }
+
+    // The live loans at the point that the access conflicts with, along with their later uses.
+    fn conflicting_loans<'a, F>(&self, borrows : &'a FunctionBorrows, point : usize, is_conflicting : F) -> Option<(&'a Loan, Span)>
+        where F : Fn(usize, &Loan) -> bool {
+        for (index, loan) in borrows.loans.iter().enumerate() {
+            if loan.is_parameter || !is_conflicting(index, loan) || !self.is_live_after(index, point) {
+                continue;
+            }
+            if let Some(later_use) = self.next_use(borrows, index, point) {
+                return Some((loan, later_use));
+            }
+        }
+        None
+    }
+}
+
+// Checks that no loan is invalidated while it is still live. Places with the same variable overlap,
+// except that writing to a place does not affect the loans on the places behind it.
+pub fn check_borrows(borrows : & FunctionBorrows, analysis : & FlowAnalysis, diagnostics : &mut Vec<Diagnostic>) {
+    for (point, event) in borrows.events.iter().enumerate() {
+        match event {
+            BorrowEvent::Borrow(index) => {
+                let loan = &borrows.loans[*index];
+                let conflict = analysis.conflicting_loans(borrows, point, |other_index, other|
+                    other_index != *index && other.place.variable == loan.place.variable
+                        && (other.kind == BorrowKind::Mutable || loan.kind == BorrowKind::Mutable));
+                if let Some((other, later_use)) = conflict {
+                    diagnostics.push(conflicting_borrow(other, loan, later_use));
+                }
+            },
+            BorrowEvent::Write(place, position) => {
+                let conflict = analysis.conflicting_loans(borrows, point, |_, other|
+                    other.place.variable == place.variable && other.place.num_of_derefs <= place.num_of_derefs);
+                if let Some((other, later_use)) = conflict {
+                    diagnostics.push(Diagnostic::error(format!("cannot assign to `{}` because it is borrowed", place), *position,
+                        &format!("assignment to borrowed `{}` occurs here", place))
+                        .with_code("E0506")
+                        .with_label(other.position, &format!("borrow of `{}` occurs here", other.place))
+                        .with_label(later_use, "borrow later used here"));
+                }
+            },
+            BorrowEvent::Read(place, position) => {
+                let conflict = analysis.conflicting_loans(borrows, point, |_, other|
+                    other.place.variable == place.variable && other.kind == BorrowKind::Mutable);
+                if let Some((other, later_use)) = conflict {
+                    diagnostics.push(Diagnostic::error(format!("cannot use `{}` because it was mutably borrowed", place), *position,
+                        &format!("use of borrowed `{}`", other.place))
+                        .with_code("E0503")
+                        .with_label(other.position, &format!("`{}` is borrowed here", other.place))
+                        .with_label(later_use, "borrow later used here"));
+                }
+            },
+            BorrowEvent::Move(place, position) => {
+                let conflict = analysis.conflicting_loans(borrows, point, |_, other| other.place.variable == place.variable);
+                if let Some((other, later_use)) = conflict {
+                    diagnostics.push(Diagnostic::error(format!("cannot move out of `{}` because it is borrowed", place), *position,
+                        &format!("move out of `{}` occurs here", place))
+                        .with_code("E0505")
+                        .with_label(other.position, &format!("borrow of `{}` occurs here", other.place))
+                        .with_label(later_use, "borrow later used here"));
+                }
+            },
+            BorrowEvent::StorageDead(variable, position) => {
+                let conflict = analysis.conflicting_loans(borrows, point, |_, other|
+                    &other.place.variable == variable && other.place.num_of_derefs == 0);
+                if let Some((other, later_use)) = conflict {
+                    let name = source_name(variable);
+                    diagnostics.push(Diagnostic::error(format!("`{}` does not live long enough", name), other.position,
+                        "borrowed value does not live long enough")
+                        .with_code("E0597")
+                        .with_label(*position, &format!("`{}` dropped here while still borrowed", name))
+                        .with_label(later_use, "borrow later used here"));
+                }
+            },
+            BorrowEvent::Use(..) | BorrowEvent::Hold(..) | BorrowEvent::StorageLive(..) | BorrowEvent::Branch => (),
+        }
+    }
+}
+
+fn conflicting_borrow(first : & Loan, second : & Loan, later_use : Span) -> Diagnostic {
+    if first.kind == BorrowKind::Mutable && second.kind == BorrowKind::Mutable {
+        return Diagnostic::error(format!("cannot borrow `{}` as mutable more than once at a time", second.place), second.position,
+            "second mutable borrow occurs here")
+            .with_code("E0499")
+            .with_label(first.position, "first mutable borrow occurs here")
+            .with_label(later_use, "first borrow later used here");
+    }
+    Diagnostic::error(format!("cannot borrow `{}` as {} because it is also borrowed as {}",
+        second.place, borrow_kind_name(second.kind), borrow_kind_name(first.kind)), second.position,
+        &format!("{} borrow occurs here", borrow_kind_name(second.kind)))
+        .with_code("E0502")
+        .with_label(first.position, &format!("{} borrow occurs here", borrow_kind_name(first.kind)))
+        .with_label(later_use, &format!("{} borrow later used here", borrow_kind_name(first.kind)))
+}
+
+fn borrow_kind_name(kind : BorrowKind) -> &'static str {
+    match kind {
+        BorrowKind::Shared => "immutable",
+        BorrowKind::Mutable => "mutable",
+    }
+}
@@ -0,0 +1,108 @@
+use super::borrows::{BorrowEvent, FunctionBorrows};
+use crate::parser::ast::{Span, source_name};
+use crate::diagnostic::Diagnostic;
+use std::collections::{HashMap, HashSet};
+
+// A variable declared without a value has to be assigned to before it is used, on every path to
+// the use. Unless it is mutable, it can only be assigned to once, on every path through the
+// function, loops included.
+
+// The variables that may be, and that may not be, assigned to before every point.
+struct Initialization<'a> {
+    is_assigned_before : Vec<HashSet<&'a String>>,
+    is_unassigned_before : Vec<HashSet<&'a String>>,
+}
+
+// Each variable is only reported once.
+pub fn check_initialization(borrows : & FunctionBorrows, diagnostics : &mut Vec<Diagnostic>) {
+    let mut declarations : HashMap<&String, (bool, Span)> = HashMap::new();
+    for event in &borrows.events {
+        if let BorrowEvent::StorageLive(variable, is_mutable, position) = event {
+            declarations.insert(variable, (*is_mutable, *position));
+        }
+    }
+    if declarations.is_empty() {
+        return;
+    }
+
+    let initialization = analyse(borrows);
+    let mut is_reported : HashSet<&String> = HashSet::new();
+    for (point, event) in borrows.events.iter().enumerate() {
+        let (variable, position, action) = match event {
+            BorrowEvent::Read(place, position) => (&place.variable, *position, "used"),
+            BorrowEvent::Write(place, position) if place.num_of_derefs > 0 => (&place.variable, *position, "used"),
+            BorrowEvent::Borrow(index) => (&borrows.loans[*index].place.variable, borrows.loans[*index].position, "borrowed"),
+            BorrowEvent::Write(place, position) => {
+                if let Some((false, declared_at)) = declarations.get(&place.variable) {
+                    if initialization.is_assigned_before[point].contains(&place.variable) && is_reported.insert(&place.variable) {
+                        let name = source_name(&place.variable);
+                        diagnostics.push(Diagnostic::error(format!("cannot assign twice to immutable variable `{}`", name), *position,
+                            "cannot assign twice to immutable variable")
+                            .with_code("E0384")
+                            .with_suggestion("consider making this binding mutable", *declared_at, &format!("mut {}", name)));
+                    }
+                }
+                continue;
+            },
+            _ => continue,
+        };
+        let declared_at = match declarations.get(variable) {
+            Some((_, declared_at)) => declared_at,
+            None => continue,
+        };
+        if initialization.is_unassigned_before[point].contains(variable) && is_reported.insert(variable) {
+            let name = source_name(variable);
+            let state = match initialization.is_assigned_before[point].contains(variable) {
+                true => "is possibly-uninitialized",
+                false => "isn't initialized",
+            };
+            diagnostics.push(Diagnostic::error(format!("{} binding `{}` {}", action, name, state), position,
+                &format!("`{}` {} here but it {}", name, action, state))
+                .with_code("E0381")
+                .with_label(*declared_at, "binding declared here but left uninitialized")
+                .with_help(&format!("consider assigning a value: `let {} = ...;`", name)));
+        }
+    }
+}
+
+// Forwards, as the union of the states along every path to a point.
+fn analyse(borrows : & FunctionBorrows) -> Initialization<'_> {
+    let successors = borrows.successors();
+    let num_of_points = borrows.events.len();
+    let mut is_assigned_before : Vec<HashSet<&String>> = vec![HashSet::new(); num_of_points];
+    let mut is_unassigned_before : Vec<HashSet<&String>> = vec![HashSet::new(); num_of_points];
+    let mut is_changed = true;
+    while is_changed {
+        is_changed = false;
+        for point in 0..num_of_points {
+            let mut is_assigned = is_assigned_before[point].clone();
+            let mut is_unassigned = is_unassigned_before[point].clone();
+            match &borrows.events[point] {
+                BorrowEvent::StorageLive(variable, _, _) => {
+                    is_assigned.remove(variable);
+                    is_unassigned.insert(variable);
+                },
+                BorrowEvent::Hold(variable, _) => {
+                    is_assigned.insert(variable);
+                    is_unassigned.remove(variable);
+                },
+                BorrowEvent::StorageDead(variable, _) => {
+                    is_assigned.remove(variable);
+                    is_unassigned.remove(variable);
+                },
+                _ => (),
+            }
+            for &successor in &successors[point] {
+                if !is_assigned.is_subset(&is_assigned_before[successor]) {
+                    is_assigned_before[successor].extend(is_assigned.iter().copied());
+                    is_changed = true;
+                }
+                if !is_unassigned.is_subset(&is_unassigned_before[successor]) {
+                    is_unassigned_before[successor].extend(is_unassigned.iter().copied());
+                    is_changed = true;
+                }
+            }
+        }
+    }
+    Initialization { is_assigned_before, is_unassigned_before }
+}
@@ -0,0 +1,103 @@
+use super::borrows::{BorrowEvent, FunctionBorrows, Place};
+use crate::parser::ast::{Span, source_name};
+use crate::diagnostic::Diagnostic;
+use std::collections::HashSet;
+
+// Moving a value out of a variable leaves it without one until it is assigned to again, so it
+// cannot be used on any path from the move, including the paths that go around a loop.
+
+// The moves, by the variable moved out of and the point of the move, that may have happened
+// before every point.
+struct Moves<'a> {
+    may_be_moved_before : Vec<HashSet<(&'a String, usize)>>,
+}
+
+const IN_PREVIOUS_ITERATION : &str = "value moved here, in previous iteration of loop";
+
+// Each variable is only reported once.
+pub fn check_moves(borrows : & FunctionBorrows, diagnostics : &mut Vec<Diagnostic>) {
+    if !borrows.events.iter().any(|event| matches!(event, BorrowEvent::Move(..))) {
+        return;
+    }
+
+    let moves = analyse(borrows);
+    let mut is_reported : HashSet<&String> = HashSet::new();
+    for (point, event) in borrows.events.iter().enumerate() {
+        let (variable, position, is_borrow) = match event {
+            BorrowEvent::Read(place, position) | BorrowEvent::Move(place, position) => (&place.variable, *position, false),
+            BorrowEvent::Write(place, position) if place.num_of_derefs > 0 => (&place.variable, *position, false),
+            BorrowEvent::Borrow(index) => (&borrows.loans[*index].place.variable, borrows.loans[*index].position, true),
+            _ => continue,
+        };
+        let mut moved_at : Vec<usize> = moves.may_be_moved_before[point].iter()
+            .filter(|(moved, _)| *moved == variable)
+            .map(|(_, move_point)| *move_point)
+            .collect();
+        if moved_at.is_empty() || !is_reported.insert(variable) {
+            continue;
+        }
+        moved_at.sort();
+
+        let name = source_name(variable);
+        let (action, participle) = if is_borrow { ("borrow", "borrowed") } else { ("use", "used") };
+        // a value moved by the use itself was moved by it in the previous iteration of a loop.
+        let label = match moved_at.iter().any(|&move_point| move_position(borrows, move_point).byte_range == position.byte_range) {
+            true => String::from(IN_PREVIOUS_ITERATION),
+            false => format!("value {} here after move", participle),
+        };
+        let mut diagnostic = Diagnostic::error(format!("{} of moved value: `{}`", action, name), position, &label)
+            .with_code("E0382");
+        if let Some((own_type, declared_at)) = borrows.declarations.get(variable) {
+            diagnostic = diagnostic.with_label(*declared_at, &format!("move occurs because `{}` has type `{}`, which does not implement the `Copy` trait",
+                name, own_type));
+        }
+        for move_point in moved_at {
+            let moved_at = move_position(borrows, move_point);
+            // only a loop leads back to a move recorded at or after the use.
+            match move_point >= point {
+                _ if moved_at.byte_range == position.byte_range => (),
+                true => diagnostic = diagnostic.with_label(moved_at, IN_PREVIOUS_ITERATION),
+                false => diagnostic = diagnostic.with_label(moved_at, "value moved here"),
+            }
+        }
+        diagnostics.push(diagnostic);
+    }
+}
+
+fn move_position(borrows : & FunctionBorrows, point : usize) -> Span {
+    match &borrows.events[point] {
+        BorrowEvent::Move(_, position) => *position,
+        _ => panic!("not a move"),
+    }
+}
+
+// Forwards, as the union of the moves along every path to a point. Assigning to a variable, or
+// declaring it anew, gives it a value again.
+fn analyse(borrows : & FunctionBorrows) -> Moves<'_> {
+    let successors = borrows.successors();
+    let num_of_points = borrows.events.len();
+    let mut may_be_moved_before : Vec<HashSet<(&String, usize)>> = vec![HashSet::new(); num_of_points];
+    let mut is_changed = true;
+    while is_changed {
+        is_changed = false;
+        for point in 0..num_of_points {
+            let mut may_be_moved = may_be_moved_before[point].clone();
+            match &borrows.events[point] {
+                BorrowEvent::Move(Place { variable, num_of_derefs : 0 }, _) => {
+                    may_be_moved.retain(|(moved, _)| *moved != variable);
+                    may_be_moved.insert((variable, point));
+                },
+                BorrowEvent::Hold(variable, _) | BorrowEvent::StorageLive(variable, _, _) | BorrowEvent::StorageDead(variable, _) =>
+                    may_be_moved.retain(|(moved, _)| *moved != variable),
+                _ => (),
+            }
+            for &successor in &successors[point] {
+                if !may_be_moved.is_subset(&may_be_moved_before[successor]) {
+                    may_be_moved_before[successor].extend(may_be_moved.iter().copied());
+                    is_changed = true;
+                }
+            }
+        }
+    }
+    Moves { may_be_moved_before }
+}
@@ -0,0 +1,789 @@
+pub mod error;
+pub mod heap;
+
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Formatter};
+use std::rc::{Rc, Weak};
+use crate::compiler::instructions::Instruction;
+use crate::format_string::{self, Segment};
+use error::Error;
+use heap::Heap;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+    Unit,
+    Unassigned,
+    // An owned String, which is the sole owner of the heap cell at the given address.
+    String(usize),
+    // A &str, which borrows the heap cell at the given address.
+    Str(usize),
+    // A reference to a variable, which is kept alive for as long as the reference exists.
+    Ref(Location),
+    // A closure is stored in the frame it captures, so it does not keep that frame alive. It
+    // cannot outlive the frame, as functions cannot be returned or passed as arguments.
+    Closure {
+        address: usize,
+        num_of_declarations: usize,
+        env: Weak<Environment>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Location {
+    env: Frame,
+    index: usize,
+}
+
+// The frame a reference points into. A reference to a variable may be stored in the frame of the
+// variable, so it does not keep that frame alive, which the borrow checker makes safe. A temporary
+// is owned by the references to it.
+#[derive(Debug, Clone)]
+enum Frame {
+    Variable(Weak<Environment>),
+    Temporary(Rc<Environment>),
+}
+
+impl Location {
+    fn env(&self) -> Option<Rc<Environment>> {
+        match &self.env {
+            Frame::Variable(env) => env.upgrade(),
+            Frame::Temporary(env) => Some(Rc::clone(env)),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Unit => write!(f, "()"),
+            Value::Unassigned => write!(f, "<unassigned>"),
+            Value::String(address) => write!(f, "<String@{}>", address),
+            Value::Str(address) => write!(f, "<&str@{}>", address),
+            Value::Ref(location) => write!(f, "<&{}>", location.index),
+            Value::Closure { address, .. } => write!(f, "<closure@{}>", address),
+        }
+    }
+}
+
+// An environment frame owns the slots [base, base + slots.len()) of the flat index space
+// that the compiler's index table assigns, and defers every lower index to its parent.
+pub struct Environment {
+    base: usize,
+    slots: RefCell<Vec<Value>>,
+    parent: Option<Rc<Environment>>,
+}
+
+impl Debug for Environment {
+    // Closures stored in an environment refer back to it, so the frame chain is not printed.
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Environment {{ base: {}, size: {} }}", self.base, self.slots.borrow().len())
+    }
+}
+
+impl Environment {
+    fn root() -> Rc<Environment> {
+        Rc::new(Environment {
+            base: 0,
+            slots: RefCell::new(vec![]),
+            parent: None,
+        })
+    }
+
+    // A detached frame holding a single temporary value.
+    fn temporary(value: Value) -> Rc<Environment> {
+        Rc::new(Environment {
+            base: 0,
+            slots: RefCell::new(vec![value]),
+            parent: None,
+        })
+    }
+
+    fn extend(parent: &Rc<Environment>, num_of_declarations: usize) -> Rc<Environment> {
+        Rc::new(Environment {
+            base: parent.size(),
+            slots: RefCell::new(vec![Value::Unassigned; num_of_declarations]),
+            parent: Some(Rc::clone(parent)),
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.base + self.slots.borrow().len()
+    }
+
+    // The frame in this one's chain that owns the slot at the given index.
+    fn frame_of(self: &Rc<Self>, index: usize) -> Option<&Rc<Environment>> {
+        let mut frame = self;
+        while index < frame.base {
+            frame = frame.parent.as_ref()?;
+        }
+        match index < frame.size() {
+            true => Some(frame),
+            false => None,
+        }
+    }
+
+    fn get(self: &Rc<Self>, index: usize) -> Option<Value> {
+        self.frame_of(index)
+            .map(|frame| frame.slots.borrow()[index - frame.base].clone())
+    }
+
+    fn set(self: &Rc<Self>, index: usize, value: Value) -> Option<()> {
+        self.frame_of(index)
+            .map(|frame| frame.slots.borrow_mut()[index - frame.base] = value)
+    }
+}
+
+struct RuntimeFrame {
+    return_address: usize,
+    env: Rc<Environment>,
+    operand_stack: Vec<Value>,
+    // Only frames pushed by LOOP can be continued, from the start of the loop.
+    continue_address: Option<usize>,
+    temporaries: Vec<usize>,
+}
+
+struct Machine<'a> {
+    bytecode: &'a [Instruction],
+    pc: usize,
+    env: Rc<Environment>,
+    operand_stack: Vec<Value>,
+    runtime_stack: Vec<RuntimeFrame>,
+    heap: Heap,
+    // The heap addresses of the temporaries of the statement being run, in order of creation.
+    temporaries: Vec<usize>,
+    // Whether each drop is reported on stderr as it happens.
+    trace_drops: bool,
+}
+
+pub fn run(bytecode: &[Instruction], trace_drops: bool) -> Result<Value> {
+    let mut machine = Machine {
+        bytecode,
+        pc: 0,
+        env: Environment::root(),
+        operand_stack: vec![],
+        runtime_stack: vec![],
+        heap: Heap::default(),
+        temporaries: vec![],
+        trace_drops,
+    };
+    machine.execute()
+}
+
+impl<'a> Machine<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error {
+            message: message.into(),
+            pc: self.pc,
+        })
+    }
+
+    // The address of the instruction the given offset from the current one.
+    fn target(&self, offset: usize) -> Result<usize> {
+        match self.pc.checked_add(offset) {
+            Some(address) => Ok(address),
+            None => self.error(format!("Jump by {} is out of the bytecode's bounds", offset)),
+        }
+    }
+
+    fn pop(&mut self) -> Result<Value> {
+        match self.operand_stack.pop() {
+            Some(value) => Ok(value),
+            None => self.error("Operand stack underflow"),
+        }
+    }
+
+    // Follows references until a value that is not a reference is reached.
+    fn deref_fully(&self, value: Value) -> Result<Value> {
+        let mut value = value;
+        while let Value::Ref(location) = value {
+            value = self.read_location(&location)?;
+        }
+        Ok(value)
+    }
+
+    fn read_location(&self, location: &Location) -> Result<Value> {
+        let env = match location.env() {
+            Some(env) => env,
+            None => return self.error("Dereference of a reference to a variable that was freed"),
+        };
+        match env.get(location.index) {
+            Some(Value::Unassigned) => self.error("Dereference of a reference to an unassigned variable"),
+            Some(value) => Ok(value),
+            None => self.error(format!("Reference to index {} is out of the environment's bounds", location.index)),
+        }
+    }
+
+    // Operands of primitive operations are dereferenced implicitly.
+    fn pop_deref(&mut self) -> Result<Value> {
+        let value = self.pop()?;
+        self.deref_fully(value)
+    }
+
+    fn pop_int(&mut self) -> Result<i64> {
+        match self.pop_deref()? {
+            Value::Int(value) => Ok(value),
+            value => self.error(format!("Expected an integer operand, found {}", value)),
+        }
+    }
+
+    fn pop_bool(&mut self) -> Result<bool> {
+        match self.pop_deref()? {
+            Value::Bool(value) => Ok(value),
+            value => self.error(format!("Expected a boolean operand, found {}", value)),
+        }
+    }
+
+    fn pop_string(&mut self) -> Result<usize> {
+        match self.pop_deref()? {
+            Value::String(address) => Ok(address),
+            value => self.error(format!("Expected a String operand, found {}", value)),
+        }
+    }
+
+    // Both an owned String and a &str can be read from.
+    fn pop_readable_string(&mut self) -> Result<usize> {
+        match self.pop_deref()? {
+            Value::String(address) | Value::Str(address) => Ok(address),
+            value => self.error(format!("Expected a String or &str operand, found {}", value)),
+        }
+    }
+
+    fn read_string(&self, address: usize) -> Result<&String> {
+        match self.heap.get(address) {
+            Some(string) => Ok(string),
+            None => self.error(format!("Use of freed heap memory at address {}", address)),
+        }
+    }
+
+    fn pop_args(&mut self, num_of_args: usize) -> Result<Vec<Value>> {
+        match self.operand_stack.len().checked_sub(num_of_args) {
+            Some(split_at) => Ok(self.operand_stack.split_off(split_at)),
+            None => self.error("Operand stack underflow"),
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        self.operand_stack.push(value);
+    }
+
+    fn arithmetic(&mut self, operation: fn(i64, i64) -> Option<i64>, name: &str) -> Result<()> {
+        let second_operand = self.pop_int()?;
+        let first_operand = self.pop_int()?;
+        match operation(first_operand, second_operand) {
+            Some(result) => {
+                self.push(Value::Int(result));
+                Ok(())
+            },
+            None => self.error(format!("Attempt to {} with overflow", name)),
+        }
+    }
+
+    // The divisor is checked once it is dereferenced, as it may be reached through a reference.
+    fn division(&mut self, operation: fn(i64, i64) -> Option<i64>, name: &str, by_zero: &str) -> Result<()> {
+        let divisor = self.pop_int()?;
+        if divisor == 0 {
+            return self.error(by_zero);
+        }
+        self.push(Value::Int(divisor));
+        self.arithmetic(operation, name)
+    }
+
+    fn comparison(&mut self, operation: fn(&i64, &i64) -> bool) -> Result<()> {
+        let second_operand = self.pop_int()?;
+        let first_operand = self.pop_int()?;
+        self.push(Value::Bool(operation(&first_operand, &second_operand)));
+        Ok(())
+    }
+
+    fn logical(&mut self, operation: fn(bool, bool) -> bool) -> Result<()> {
+        let second_operand = self.pop_bool()?;
+        let first_operand = self.pop_bool()?;
+        self.push(Value::Bool(operation(first_operand, second_operand)));
+        Ok(())
+    }
+
+    fn equal(&mut self) -> Result<()> {
+        let second_operand = self.pop_deref()?;
+        let first_operand = self.pop_deref()?;
+        let result = match (first_operand, second_operand) {
+            (Value::Int(x), Value::Int(y)) => x == y,
+            (Value::Bool(x), Value::Bool(y)) => x == y,
+            (Value::Unit, Value::Unit) => true,
+            (Value::String(x) | Value::Str(x), Value::String(y) | Value::Str(y)) =>
+                self.read_string(x)? == self.read_string(y)?,
+            (x, y) => return self.error(format!("Cannot compare {} with {}", x, y)),
+        };
+        self.push(Value::Bool(result));
+        Ok(())
+    }
+
+    fn load(&mut self, index: usize) -> Result<()> {
+        match self.env.get(index) {
+            Some(Value::Unassigned) => self.error(format!("Use of unassigned variable at index {}", index)),
+            Some(value) => {
+                self.push(value);
+                Ok(())
+            },
+            None => self.error(format!("Variable index {} is out of the environment's bounds", index)),
+        }
+    }
+
+    // Moving a value out of a variable leaves it unassigned, so that dropping it does nothing.
+    fn move_out(&mut self, index: usize) -> Result<()> {
+        self.load(index)?;
+        self.env.set(index, Value::Unassigned);
+        Ok(())
+    }
+
+    fn assign(&mut self, index: usize) -> Result<()> {
+        let value = self.pop()?;
+        match self.env.set(index, value) {
+            Some(()) => Ok(()),
+            None => self.error(format!("Variable index {} is out of the environment's bounds", index)),
+        }
+    }
+
+    fn format_value(&self, value: Value, is_debug: bool) -> Result<String> {
+        match self.deref_fully(value)? {
+            Value::String(address) | Value::Str(address) => {
+                let string = self.read_string(address)?;
+                match is_debug {
+                    true => Ok(format!("{:?}", string)),
+                    false => Ok(string.clone()),
+                }
+            },
+            value => Ok(value.to_string()),
+        }
+    }
+
+    fn println(&mut self, num_of_args: usize) -> Result<()> {
+        let args = self.pop_args(num_of_args)?;
+        let format_address = self.pop_readable_string()?;
+
+        let segments = match format_string::parse(self.read_string(format_address)?) {
+            Ok(segments) => segments,
+            Err(message) => return self.error(message),
+        };
+        let num_of_placeholders = format_string::count_placeholders(&segments);
+        if num_of_placeholders != num_of_args {
+            return self.error(format!(
+                "println has {} placeholder(s) but {} argument(s) were supplied",
+                num_of_placeholders, num_of_args));
+        }
+
+        let mut args = args.into_iter();
+        let mut output = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Literal(literal) => output.push_str(&literal),
+                Segment::Display => output.push_str(&self.format_value(args.next().unwrap(), false)?),
+                Segment::Debug => output.push_str(&self.format_value(args.next().unwrap(), true)?),
+            }
+        }
+
+        println!("{}", output);
+        self.push(Value::Unit);
+        Ok(())
+    }
+
+    // The reference points into the frame that owns the variable, which outlives the blocks that
+    // borrow from it.
+    fn borrow(&mut self, index: usize) -> Result<()> {
+        let frame = match self.env.frame_of(index) {
+            Some(frame) => Rc::downgrade(frame),
+            None => return self.error(format!("Variable index {} is out of the environment's bounds", index)),
+        };
+        let location = Location {
+            env: Frame::Variable(frame),
+            index,
+        };
+        self.read_location(&location)?;
+        self.push(Value::Ref(location));
+        Ok(())
+    }
+
+    fn borrow_temporary(&mut self) -> Result<()> {
+        let value = self.pop()?;
+        self.push(Value::Ref(Location {
+            env: Frame::Temporary(Environment::temporary(value)),
+            index: 0,
+        }));
+        Ok(())
+    }
+
+    fn pop_ref(&mut self) -> Result<Location> {
+        match self.pop()? {
+            Value::Ref(location) => Ok(location),
+            value => self.error(format!("Expected a reference operand, found {}", value)),
+        }
+    }
+
+    fn dereference(&mut self) -> Result<()> {
+        let location = self.pop_ref()?;
+        let value = self.read_location(&location)?;
+        self.push(value);
+        Ok(())
+    }
+
+    fn assign_through_reference(&mut self) -> Result<()> {
+        let location = self.pop_ref()?;
+        let value = self.pop()?;
+        let env = match location.env() {
+            Some(env) => env,
+            None => return self.error("Assignment through a reference to a variable that was freed"),
+        };
+        match env.set(location.index, value) {
+            Some(()) => Ok(()),
+            None => self.error(format!("Reference to index {} is out of the environment's bounds", location.index)),
+        }
+    }
+
+    fn string_from(&mut self) -> Result<()> {
+        let address = self.pop_readable_string()?;
+        let string = self.read_string(address)?.clone();
+        let new_address = self.heap.allocate(string);
+        self.push(Value::String(new_address));
+        Ok(())
+    }
+
+    fn len(&mut self) -> Result<()> {
+        let address = self.pop_readable_string()?;
+        let len = self.read_string(address)?.len();
+        self.push(Value::Int(len as i64));
+        Ok(())
+    }
+
+    fn as_str(&mut self) -> Result<()> {
+        let address = self.pop_string()?;
+        self.read_string(address)?;
+        self.push(Value::Str(address));
+        Ok(())
+    }
+
+    fn push_str(&mut self) -> Result<()> {
+        let source_address = self.pop_readable_string()?;
+        let target_address = self.pop_string()?;
+        let source = self.read_string(source_address)?.clone();
+        match self.heap.get_mut(target_address) {
+            Some(target) => target.push_str(&source),
+            None => return self.error(format!("Use of freed heap memory at address {}", target_address)),
+        }
+        self.push(Value::Unit);
+        Ok(())
+    }
+
+    // Ends the lifetime of the variable at the given index, freeing the heap cell it owns, if any.
+    // A variable that was already dropped is left as is.
+    fn drop(&mut self, index: usize) -> Result<()> {
+        let value = match self.env.get(index) {
+            Some(value) => value,
+            None => return self.error(format!("Variable index {} is out of the environment's bounds", index)),
+        };
+        self.env.set(index, Value::Unassigned);
+
+        if let Value::String(address) = value {
+            match self.heap.free(address) {
+                Some(string) => if self.trace_drops {
+                    eprintln!("[drop] freed String {:?} at heap address {} (variable index {})", string, address, index);
+                },
+                None => return self.error(format!("Double free of heap memory at address {}", address)),
+            }
+        }
+        Ok(())
+    }
+
+    fn keep_temporary(&mut self) -> Result<()> {
+        match self.operand_stack.last() {
+            Some(Value::String(address)) => self.temporaries.push(*address),
+            Some(value) => return self.error(format!("Expected a String to keep as a temporary, found {}", value)),
+            None => return self.error("Operand stack underflow"),
+        }
+        Ok(())
+    }
+
+    // Frees the given temporaries, the latest first.
+    fn drop_temporaries(&mut self, temporaries: Vec<usize>) -> Result<()> {
+        for address in temporaries.into_iter().rev() {
+            match self.heap.free(address) {
+                Some(string) => if self.trace_drops {
+                    eprintln!("[drop] freed String {:?} at heap address {} (temporary)", string, address);
+                },
+                None => return self.error(format!("Double free of heap memory at address {}", address)),
+            }
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, num_of_args: usize) -> Result<()> {
+        let args = self.pop_args(num_of_args)?;
+
+        let (address, num_of_declarations, closure_env) = match self.pop()? {
+            Value::Closure { address, num_of_declarations, env } => (address, num_of_declarations, env),
+            value => return self.error(format!("Expected a closure to call, found {}", value)),
+        };
+        let closure_env = match closure_env.upgrade() {
+            Some(env) => env,
+            None => return self.error("Call of a function whose environment was freed"),
+        };
+        if num_of_args > num_of_declarations {
+            return self.error(format!(
+                "Function expects at most {} arguments but {} were supplied",
+                num_of_declarations, num_of_args));
+        }
+
+        let new_env = Environment::extend(&closure_env, num_of_declarations);
+        new_env.slots.borrow_mut().splice(0..num_of_args, args);
+
+        self.runtime_stack.push(RuntimeFrame {
+            return_address: self.pc + 1,
+            env: std::mem::replace(&mut self.env, new_env),
+            operand_stack: std::mem::take(&mut self.operand_stack),
+            continue_address: None,
+            temporaries: std::mem::take(&mut self.temporaries),
+        });
+        self.pc = address;
+        Ok(())
+    }
+
+    fn enter_loop(&mut self, offset: usize) -> Result<()> {
+        self.runtime_stack.push(RuntimeFrame {
+            return_address: self.target(offset)?,
+            env: Rc::clone(&self.env),
+            operand_stack: std::mem::take(&mut self.operand_stack),
+            continue_address: Some(self.pc + 1),
+            temporaries: std::mem::take(&mut self.temporaries),
+        });
+        self.pc += 1;
+        Ok(())
+    }
+
+    // Pops the given number of frames and restores the state from before the outermost of them
+    // was pushed. Returns the return and continue addresses of the outermost frame. The statements
+    // left within the frames never end, so their temporaries are freed.
+    fn unwind(&mut self, num_of_frames: usize) -> Result<(usize, Option<usize>)> {
+        if num_of_frames == 0 || num_of_frames > self.runtime_stack.len() {
+            return self.error("Runtime stack underflow");
+        }
+        let split_at = self.runtime_stack.len() - num_of_frames;
+        let mut frames = self.runtime_stack.drain(split_at..);
+        let frame = frames.next().unwrap();
+        let mut temporaries: Vec<usize> = frames.flat_map(|frame| frame.temporaries).collect();
+        temporaries.append(&mut self.temporaries);
+        self.drop_temporaries(temporaries)?;
+
+        self.env = frame.env;
+        self.operand_stack = frame.operand_stack;
+        self.temporaries = frame.temporaries;
+        Ok((frame.return_address, frame.continue_address))
+    }
+
+    fn break_loop(&mut self, num_of_frames: usize) -> Result<()> {
+        let value = self.pop()?;
+        match self.unwind(num_of_frames + 1)? {
+            (return_address, Some(_)) => self.pc = return_address,
+            (_, None) => return self.error("Break executed outside of a loop"),
+        }
+        self.push(value);
+        Ok(())
+    }
+
+    fn continue_loop(&mut self, num_of_frames: usize) -> Result<()> {
+        if num_of_frames > 0 {
+            self.unwind(num_of_frames)?;
+        }
+        let continue_address = match self.runtime_stack.last() {
+            Some(RuntimeFrame { continue_address: Some(address), .. }) => *address,
+            _ => return self.error("Continue executed outside of a loop"),
+        };
+        let temporaries = std::mem::take(&mut self.temporaries);
+        self.drop_temporaries(temporaries)?;
+        self.operand_stack.clear();
+        self.pc = continue_address;
+        Ok(())
+    }
+
+    // The temporaries of the value of a block are left to the statement the block is in.
+    fn return_from_call(&mut self) -> Result<()> {
+        let return_value = self.operand_stack.pop().unwrap_or(Value::Unit);
+        let frame = match self.runtime_stack.pop() {
+            Some(frame) => frame,
+            None => return self.error("Return instruction executed outside of a function call"),
+        };
+
+        self.pc = frame.return_address;
+        self.env = frame.env;
+        self.operand_stack = frame.operand_stack;
+        let temporaries = std::mem::replace(&mut self.temporaries, frame.temporaries);
+        self.temporaries.extend(temporaries);
+        self.push(return_value);
+        Ok(())
+    }
+
+    // Leaves the frames of the blocks and loops within the function along with its call, and the
+    // statement the `return` is in along with them.
+    fn return_early(&mut self, num_of_frames: usize) -> Result<()> {
+        if num_of_frames > 0 {
+            let return_value = self.pop()?;
+            self.unwind(num_of_frames)?;
+            self.push(return_value);
+        }
+        let temporaries = std::mem::take(&mut self.temporaries);
+        self.drop_temporaries(temporaries)?;
+        self.return_from_call()
+    }
+
+    fn execute(&mut self) -> Result<Value> {
+        loop {
+            let instruction = match self.bytecode.get(self.pc) {
+                Some(instruction) => instruction,
+                None => return self.error("Program counter is out of the bytecode's bounds"),
+            };
+
+            match instruction {
+                Instruction::START => {
+                    self.env = Environment::root();
+                    self.operand_stack.clear();
+                    self.runtime_stack.clear();
+                    self.temporaries.clear();
+                },
+                Instruction::LDCI(value) => self.push(Value::Int(*value)),
+                Instruction::LDCB(value) => self.push(Value::Bool(*value)),
+                Instruction::LDCU => self.push(Value::Unit),
+                Instruction::LDCS(value) => {
+                    let address = self.heap.intern(value);
+                    self.push(Value::Str(address));
+                },
+                Instruction::LD(index) => self.load(*index)?,
+                Instruction::PLUS => self.arithmetic(i64::checked_add, "add")?,
+                Instruction::MINUS => self.arithmetic(i64::checked_sub, "subtract")?,
+                Instruction::TIMES => self.arithmetic(i64::checked_mul, "multiply")?,
+                Instruction::DIV => self.division(i64::checked_div, "divide", "Attempt to divide by zero")?,
+                Instruction::MOD => self.division(i64::checked_rem, "calculate the remainder",
+                    "Attempt to calculate the remainder with a divisor of zero")?,
+                Instruction::EQUAL => self.equal()?,
+                Instruction::GREATER => self.comparison(i64::gt)?,
+                Instruction::GEQ => self.comparison(i64::ge)?,
+                Instruction::LESS => self.comparison(i64::lt)?,
+                Instruction::LEQ => self.comparison(i64::le)?,
+                Instruction::AND => self.logical(|x, y| x && y)?,
+                Instruction::OR => self.logical(|x, y| x || y)?,
+                Instruction::NOT => {
+                    let operand = self.pop_bool()?;
+                    self.push(Value::Bool(!operand));
+                },
+                Instruction::UMINUS => {
+                    let operand = self.pop_int()?;
+                    match operand.checked_neg() {
+                        Some(result) => self.push(Value::Int(result)),
+                        None => return self.error("Attempt to negate with overflow"),
+                    }
+                },
+                Instruction::STRFROM => self.string_from()?,
+                Instruction::LEN => self.len()?,
+                Instruction::ASSTR => self.as_str()?,
+                Instruction::PUSHSTR => self.push_str()?,
+                Instruction::BORROW(index) | Instruction::MUTBORROW(index) => self.borrow(*index)?,
+                Instruction::TEMPBORROW => self.borrow_temporary()?,
+                Instruction::DEREF => self.dereference()?,
+                Instruction::DEREFASSIGN => self.assign_through_reference()?,
+                Instruction::DROP(index) => self.drop(*index)?,
+                Instruction::MOVE(index) => self.move_out(*index)?,
+                Instruction::TEMP => self.keep_temporary()?,
+                Instruction::DROPTEMPS => {
+                    let temporaries = std::mem::take(&mut self.temporaries);
+                    self.drop_temporaries(temporaries)?;
+                },
+                Instruction::PRINTLN(num_of_args) => self.println(*num_of_args)?,
+                Instruction::POP => {
+                    self.pop()?;
+                },
+                Instruction::GOTOR(offset) => {
+                    self.pc = self.target(*offset)?;
+                    continue;
+                },
+                Instruction::JOFR(offset) => {
+                    if !self.pop_bool()? {
+                        self.pc = self.target(*offset)?;
+                        continue;
+                    }
+                },
+                Instruction::LOOP(offset) => {
+                    self.enter_loop(*offset)?;
+                    continue;
+                },
+                Instruction::BREAK(num_of_frames) => {
+                    self.break_loop(*num_of_frames)?;
+                    continue;
+                },
+                Instruction::CONTINUE(num_of_frames) => {
+                    self.continue_loop(*num_of_frames)?;
+                    continue;
+                },
+                Instruction::ASSIGN(index) => self.assign(*index)?,
+                Instruction::LDF(_, offset, num_of_declarations) => {
+                    let closure = Value::Closure {
+                        address: self.target(*offset)?,
+                        num_of_declarations: *num_of_declarations,
+                        env: Rc::downgrade(&self.env),
+                    };
+                    self.push(closure);
+                },
+                Instruction::CALL(num_of_args) => {
+                    self.call(*num_of_args)?;
+                    continue;
+                },
+                Instruction::RTN => {
+                    self.return_from_call()?;
+                    continue;
+                },
+                Instruction::RETURN(num_of_frames) => {
+                    self.return_early(*num_of_frames)?;
+                    continue;
+                },
+                Instruction::DONE => return Ok(self.operand_stack.pop().unwrap_or(Value::Unit)),
+            }
+
+            self.pc += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compiler, parser, static_checker};
+
+    // Runs the program, and gives the number of owners its outermost frame has left besides the
+    // machine, which are the values that would leak it.
+    fn owners_of_outermost_frame(program: &str) -> usize {
+        let ast = parser::parse(program).unwrap();
+        let drop_at = static_checker::check(&ast).unwrap_or_else(|_| panic!("the program has errors"));
+        let bytecode = compiler::compile(&ast, &drop_at).unwrap();
+        let mut machine = Machine {
+            bytecode: &bytecode,
+            pc: 0,
+            env: Environment::root(),
+            operand_stack: vec![],
+            runtime_stack: vec![],
+            heap: Heap::default(),
+            temporaries: vec![],
+            trace_drops: false,
+        };
+        machine.execute().unwrap();
+        Rc::strong_count(&machine.env) - 1
+    }
+
+    #[test]
+    fn functions_do_not_keep_the_frame_they_are_stored_in_alive() {
+        let program = "
+            fn factorial(n : i64) -> i64 { if n == 0 { 1 } else { n * factorial(n - 1) } }
+            fn main() { factorial(5); }";
+        assert_eq!(owners_of_outermost_frame(program), 0);
+    }
+}
@@ -0,0 +1,15 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result;
+
+#[derive(Debug)]
+pub struct Error {
+    pub message: String,
+    pub pc: usize,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "Runtime error at instruction {}: {}", self.pc, self.message)
+    }
+}
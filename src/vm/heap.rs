@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct Heap {
+    cells: Vec<Option<String>>,
+    interned: HashMap<String, usize>,
+}
+
+impl Heap {
+    pub fn allocate(&mut self, value: String) -> usize {
+        self.cells.push(Some(value));
+        self.cells.len() - 1
+    }
+
+    // String literals have a static lifetime, so each distinct literal is allocated once.
+    pub fn intern(&mut self, value: &str) -> usize {
+        match self.interned.get(value) {
+            Some(address) => *address,
+            None => {
+                let address = self.allocate(String::from(value));
+                self.interned.insert(String::from(value), address);
+                address
+            }
+        }
+    }
+
+    pub fn get(&self, address: usize) -> Option<&String> {
+        self.cells.get(address)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, address: usize) -> Option<&mut String> {
+        self.cells.get_mut(address)?.as_mut()
+    }
+
+    // Returns the freed value, or None if the cell was never allocated or is already freed.
+    pub fn free(&mut self, address: usize) -> Option<String> {
+        self.cells.get_mut(address)?.take()
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{errors, oxido_source};
+
+// Runs a program dividing 7 by a zero reached through the reference `r`.
+fn run_with_zero_divisor(expression: &str) -> common::Output {
+    oxido_source(&["run"], &format!("
+fn main() {{
+    let x = 7;
+    let zero = 0;
+    let r = &zero;
+    println(\"{{}}\", {});
+}}", expression))
+}
+
+#[test]
+fn division_by_zero_through_a_reference_is_reported() {
+    for expression in ["x / r", "x / *r"] {
+        let output = run_with_zero_divisor(expression);
+        assert_eq!(output.status, 101);
+        assert!(output.stderr.ends_with(": Attempt to divide by zero\n"), "{}: {}", expression, output.stderr);
+    }
+}
+
+#[test]
+fn remainder_by_zero_through_a_reference_is_reported() {
+    for expression in ["x % r", "x % *r"] {
+        let output = run_with_zero_divisor(expression);
+        assert_eq!(output.status, 101);
+        assert!(output.stderr.ends_with(": Attempt to calculate the remainder with a divisor of zero\n"), "{}: {}", expression, output.stderr);
+    }
+}
+
+#[test]
+fn compound_assignment_applies_its_operator_to_the_variable() {
+    let output = oxido_source(&["run"], "
+fn main() {
+    let mut x = 10;
+    x += 5;
+    x -= 3;
+    x *= 4;
+    x /= 5;
+    x %= 7;
+    println(\"{} {} {}\", x, 17 % 5, -17 % 5);
+}");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "2 2 -2\n");
+}
+
+#[test]
+fn compound_assignment_to_an_immutable_variable_is_rejected() {
+    let output = oxido_source(&["check"], "
+fn main() {
+    let x = 1;
+    x += 1;
+}");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0384]: cannot assign twice to immutable variable `x` at 4:5",
+        "error: aborting due to previous error",
+    ]);
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{errors, example, oxido};
+
+fn check(path: &str) -> common::Output {
+    oxido(&["check", &example(path)])
+}
+
+#[test]
+fn second_mutable_borrow_is_rejected() {
+    let output = check("borrow/borrow_error.rs");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0499]: cannot borrow `s` as mutable more than once at a time at 4:17",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("------ first mutable borrow occurs here"), "{}", output.stderr);
+    assert!(output.stderr.contains("-- first borrow later used here"), "{}", output.stderr);
+}
+
+#[test]
+fn mutable_and_immutable_borrows_are_rejected_together() {
+    let output = check("borrow/borrow_multiple_error.rs");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0499]: cannot borrow `s` as mutable more than once at a time at 5:14",
+        "error[E0502]: cannot borrow `m` as mutable because it is also borrowed as immutable at 11:14",
+        "error[E0502]: cannot borrow `n` as immutable because it is also borrowed as mutable at 17:14",
+        "error: aborting due to 3 previous errors",
+    ]);
+    assert!(output.stderr.contains("-- immutable borrow later used here"), "{}", output.stderr);
+    assert!(output.stderr.contains("-- mutable borrow later used here"), "{}", output.stderr);
+}
+
+#[test]
+fn moving_a_borrowed_value_is_rejected() {
+    let output = check("borrow/borrow_dangle_error.rs");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0505]: cannot move out of `s` because it is borrowed at 6:14",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("-- borrow of `s` occurs here"), "{}", output.stderr);
+    assert!(output.stderr.contains("^ move out of `s` occurs here"), "{}", output.stderr);
+}
+
+#[test]
+fn assigning_to_a_borrowed_place_is_rejected() {
+    let output = check("weird_rust/borrow_shared_error.rs");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0506]: cannot assign to `*y` because it is borrowed at 7:5",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("^^^^^^ assignment to borrowed `*y` occurs here"), "{}", output.stderr);
+}
+
+#[test]
+fn borrows_that_do_not_overlap_are_accepted() {
+    for path in [
+        "borrow/borrow_modify.rs",
+        "borrow/borrow_multiple.rs",
+        "borrow/borrow_multiple_scope.rs",
+        "borrow/borrow_shared_mutable_ref.rs",
+        "weird_rust/borrow_chain_simpleshare_pass.rs",
+        "weird_rust/borrow_shared_pass.rs",
+        "weird_rust/borrow_simple_pass.rs",
+    ] {
+        let output = check(path);
+        assert_eq!(output.status, 0, "{}: {}", path, output.stderr);
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{build, example, oxido, oxido_with_input};
+
+#[test]
+fn built_bytecode_runs_like_the_source() {
+    let bytecode = build("drop/drop_conditional_move.rs");
+    assert!(bytecode.starts_with(b"OXBC"));
+
+    let from_source = oxido(&["run", "--trace-drops", &example("drop/drop_conditional_move.rs")]);
+    let from_bytecode = oxido_with_input(&["run", "--trace-drops", "-"], &bytecode);
+    assert_eq!(from_bytecode.status, 0, "{}", from_bytecode.stderr);
+    assert_eq!(from_bytecode.stdout, from_source.stdout);
+    assert_eq!(from_bytecode.stderr, from_source.stderr);
+}
+
+#[test]
+fn built_bytecode_disassembles_like_the_source() {
+    let bytecode = build("function/function_return.rs");
+    let from_source = oxido(&["disasm", &example("function/function_return.rs")]);
+    let from_bytecode = oxido_with_input(&["disasm", "-"], &bytecode);
+    assert_eq!(from_bytecode.status, 0, "{}", from_bytecode.stderr);
+    assert_eq!(from_bytecode.stdout, from_source.stdout);
+    assert!(from_source.stdout.starts_with(" 0  START\n"), "{}", from_source.stdout);
+}
+
+#[test]
+fn truncated_bytecode_is_rejected() {
+    let bytecode = build("function/function_return.rs");
+    let output = oxido_with_input(&["run", "-"], &bytecode[..bytecode.len() - 1]);
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.starts_with("error: `<stdin>`: Invalid bytecode at byte"), "{}", output.stderr);
+    assert!(output.stderr.ends_with("unexpected end of file\n"), "{}", output.stderr);
+}
+
+#[test]
+fn jump_past_the_end_is_rejected_rather_than_run() {
+    // START followed by a GOTOR whose target overflows.
+    let mut bytecode = b"OXBC\x01".to_vec();
+    bytecode.extend(2u64.to_le_bytes());
+    bytecode.push(0);
+    bytecode.push(32);
+    bytecode.extend(u64::MAX.to_le_bytes());
+    let output = oxido_with_input(&["run", "-"], &bytecode);
+    assert_eq!(output.status, 1);
+    assert_eq!(output.stderr, "error: `<stdin>`: Invalid bytecode at byte 14: `GOTOR 18446744073709551615` at instruction 1 leads past the last instruction\n");
+}
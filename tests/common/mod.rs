@@ -0,0 +1,85 @@
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// What running oxido printed, along with the code it exited with.
+pub struct Output {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// The path of a program in parse_examples.
+pub fn example(path: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("parse_examples").join(path).to_string_lossy().into_owned()
+}
+
+pub fn oxido(args: &[&str]) -> Output {
+    oxido_with_input(args, &[])
+}
+
+// Runs oxido with the given bytes on its standard input, which is read for the file `-`.
+pub fn oxido_with_input(args: &[&str], input: &[u8]) -> Output {
+    let output = run(args, input);
+    Output {
+        status: output.status.code().expect("oxido was killed by a signal"),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    }
+}
+
+// The bytecode built from the given example.
+pub fn build(path: &str) -> Vec<u8> {
+    let output = run(&["build", "-o", "-", &example(path)], &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output.stdout
+}
+
+fn run(args: &[&str], input: &[u8]) -> std::process::Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_oxido-lang"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start oxido");
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+// Runs the given source as a program read from standard input.
+pub fn oxido_source(args: &[&str], source: &str) -> Output {
+    let mut args = args.to_vec();
+    args.push("-");
+    oxido_with_input(&args, source.as_bytes())
+}
+
+// The values freed while running the given example, in the order they are freed.
+pub fn dropped_values(path: &str) -> (Output, Vec<String>) {
+    let output = oxido(&["run", "--trace-drops", &example(path)]);
+    let dropped = output.stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("[drop] freed String "))
+        .map(|line| line.split(" at heap address").next().unwrap().to_string())
+        .collect();
+    (output, dropped)
+}
+
+// The message of each error reported, in order, with the line and column it is located at.
+pub fn errors(stderr: &str) -> Vec<String> {
+    let lines: Vec<&str> = stderr.lines().collect();
+    lines.iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("error"))
+        .map(|(index, line)| match lines.get(index + 1).and_then(|next| next.trim_start().strip_prefix("--> ")) {
+            Some(location) => {
+                let mut parts = location.rsplitn(3, ':');
+                let (col, line_no) = (parts.next().unwrap(), parts.next().unwrap());
+                format!("{} at {}:{}", line, line_no, col)
+            },
+            None => line.to_string(),
+        })
+        .collect()
+}
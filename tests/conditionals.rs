@@ -0,0 +1,33 @@
+mod common;
+
+use common::{errors, oxido_source};
+
+#[test]
+fn if_else_chain_has_the_value_of_the_branch_taken() {
+    let output = oxido_source(&["run"], "
+fn sign(n : i64) -> i64 {
+    if n < 0 { -1 } else if n == 0 { 0 } else { 1 }
+}
+
+fn main() {
+    let label = if sign(-5) < 0 { \"negative\" } else { \"positive\" };
+    println(\"{} {} {} {}\", sign(-5), sign(0), sign(9), label);
+}");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "-1 0 1 negative\n");
+}
+
+#[test]
+fn branches_of_different_types_are_rejected() {
+    let output = oxido_source(&["check"], "
+fn main() {
+    let condition = true;
+    let number = if condition { 5 } else { \"six\" };
+}");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0308]: `if` and `else` have incompatible types at 4:42",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("expected `i64`, found `&'static str`"), "{}", output.stderr);
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use common::{errors, oxido_source};
+
+#[test]
+fn checking_goes_on_after_the_first_error() {
+    let output = oxido_source(&["check"], "
+fn main() {
+    let x : i64 = true;
+    let y = z;
+}
+
+fn other() {
+    let a = 1;
+    a = 2;
+}");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0308]: mismatched types at 3:19",
+        "error[E0425]: cannot find value `z` in this scope at 4:13",
+        "error[E0384]: cannot assign twice to immutable variable `a` at 9:5",
+        "error: aborting due to 3 previous errors",
+    ]);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::dropped_values;
+
+#[test]
+fn statements_on_the_same_line_drop_at_the_closing_brace() {
+    let (output, dropped) = dropped_values("drop/drop_same_line.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "a b\n");
+    assert_eq!(dropped, ["\"b\"", "\"a\""]);
+}
+
+#[test]
+fn moved_value_is_dropped_by_its_new_owner_only() {
+    let (output, dropped) = dropped_values("drop/drop_moved_same_line.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "a\n");
+    assert_eq!(dropped, ["\"a\""]);
+}
+
+#[test]
+fn break_and_continue_drop_the_variables_of_the_loop_body() {
+    let (output, dropped) = dropped_values("drop/drop_break_continue.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "looped\n");
+    assert_eq!(dropped, ["\"looped\""; 3]);
+}
+
+#[test]
+fn value_moved_on_one_branch_is_dropped_on_the_other() {
+    let (output, dropped) = dropped_values("drop/drop_conditional_move.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "took maybe\nkept\n");
+    assert_eq!(dropped, ["\"maybe\""; 2]);
+}
+
+#[test]
+fn parameters_of_an_empty_function_body_are_dropped() {
+    let (output, dropped) = dropped_values("drop/drop_empty_body.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(dropped, ["\"ignored\""]);
+}
+
+#[test]
+fn assigning_drops_the_previous_value() {
+    let (output, dropped) = dropped_values("drop/drop_reassign.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "second\n");
+    assert_eq!(dropped, ["\"first\"", "\"second\""]);
+}
+
+#[test]
+fn temporaries_are_dropped_at_the_end_of_their_statement() {
+    let (output, dropped) = dropped_values("drop/drop_temporaries.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "1 printed\n6\n");
+    assert_eq!(dropped, ["\"t\"", "\"discarded\"", "\"printed\"", "\"block\""]);
+}
+
+#[test]
+fn return_drops_the_variables_of_every_scope_it_leaves() {
+    let (output, dropped) = dropped_values("drop/drop_early_return.rs");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "4 5\n");
+    assert_eq!(dropped, ["\"kept\"", "\"inner\"", "\"outer\"", "\"inner\"", "\"outer\""]);
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::{example, oxido};
+use std::fs;
+
+// Every program in parse_examples, by its path within it.
+fn examples() -> Vec<String> {
+    let mut paths = vec![];
+    for directory in fs::read_dir(example("")).unwrap() {
+        let directory = directory.unwrap().path();
+        for file in fs::read_dir(&directory).unwrap() {
+            let file = file.unwrap().path();
+            paths.push(format!("{}/{}", directory.file_name().unwrap().to_string_lossy(), file.file_name().unwrap().to_string_lossy()));
+        }
+    }
+    paths.sort();
+    paths
+}
+
+// Checking never crashes, and a rejected program ends with the summary of its errors.
+#[test]
+fn every_example_is_checked_without_crashing() {
+    for path in examples() {
+        let output = oxido(&["check", "--color=never", &example(&path)]);
+        match output.status {
+            0 => assert_eq!(output.stderr, "", "{}", path),
+            1 => assert!(output.stderr.starts_with("error") && output.stderr.contains("\nerror: aborting due to "), "{}: {}", path, output.stderr),
+            status => panic!("{} exited with {}: {}", path, status, output.stderr),
+        }
+    }
+}
+
+// Other than division by zero, which is only found when the program runs.
+#[test]
+fn every_error_example_is_rejected() {
+    for path in examples().iter().filter(|path| path.ends_with("_error.rs")) {
+        let output = oxido(&["run", &example(path)]);
+        match path.as_str() {
+            "variables/divide_by_zero_error.rs" => {
+                assert_eq!(output.status, 101, "{}", output.stderr);
+                assert!(output.stderr.ends_with(": Attempt to divide by zero\n"), "{}", output.stderr);
+            },
+            path => assert_eq!(output.status, 1, "{} was accepted: {}", path, output.stdout),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{errors, oxido_source};
+
+#[test]
+fn functions_can_be_called_before_their_declaration_and_recursively() {
+    let output = oxido_source(&["run"], "
+fn main() {
+    println(\"{} {}\", is_even(10), is_odd(7));
+}
+
+fn is_even(n : i64) -> bool {
+    if n == 0 { true } else { is_odd(n - 1) }
+}
+
+fn is_odd(n : i64) -> bool {
+    if n == 0 { false } else { is_even(n - 1) }
+}");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "true true\n");
+}
+
+#[test]
+fn function_declared_after_main_is_checked() {
+    let output = oxido_source(&["check"], "
+fn main() {
+    helper();
+}
+
+fn helper() -> i64 {
+    true
+}");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0308]: mismatched types at 6:4",
+        "error: aborting due to previous error",
+    ]);
+}
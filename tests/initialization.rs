@@ -0,0 +1,14 @@
+mod common;
+
+use common::{errors, example, oxido};
+
+#[test]
+fn variable_possibly_uninitialized_is_rejected() {
+    let output = oxido(&["check", &example("variables/uninitialized_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0381]: used binding `x` is possibly-uninitialized at 6:19",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("- binding declared here but left uninitialized"), "{}", output.stderr);
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::{example, oxido};
+
+#[test]
+fn each_diagnostic_is_a_line_ending_with_the_summary() {
+    let output = oxido(&["check", "--error-format=json", &example("mutability_and_shadowing/immutability_error.rs")]);
+    assert_eq!(output.status, 1);
+    let lines: Vec<&str> = output.stderr.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", output.stderr);
+    assert!(lines[0].starts_with("{\"$message_type\":\"diagnostic\",\"message\":\"cannot assign twice to immutable variable `x`\",\
+        \"code\":{\"code\":\"E0384\",\"explanation\":null},\"level\":\"error\""), "{}", lines[0]);
+    assert_eq!(lines[1], "{\"$message_type\":\"diagnostic\",\"message\":\"aborting due to previous error\",\"code\":null,\"level\":\"error\",\
+        \"spans\":[],\"children\":[],\"rendered\":\"error: aborting due to previous error\\n\"}");
+}
+
+#[test]
+fn labels_and_suggestions_are_spans() {
+    let output = oxido(&["check", "--error-format=json", &example("mutability_and_shadowing/immutability_error.rs")]);
+    let diagnostic = output.stderr.lines().next().unwrap();
+    assert!(diagnostic.contains("\"byte_start\":72,\"byte_end\":77,\"line_start\":4,\"line_end\":4,\"column_start\":5,\"column_end\":10,\
+        \"is_primary\":true,\"text\":[{\"text\":\"    x = 6;\",\"highlight_start\":5,\"highlight_end\":10}],\
+        \"label\":\"cannot assign twice to immutable variable\""), "{}", diagnostic);
+    assert!(diagnostic.contains("\"is_primary\":false,\"text\":[{\"text\":\"    let x = 5;\",\"highlight_start\":9,\"highlight_end\":10}],\
+        \"label\":\"first assignment to `x`\""), "{}", diagnostic);
+    assert!(diagnostic.contains("\"children\":[{\"message\":\"consider making this binding mutable\",\"code\":null,\"level\":\"help\""), "{}", diagnostic);
+    assert!(diagnostic.contains("\"suggested_replacement\":\"mut x\",\"suggestion_applicability\":\"MachineApplicable\""), "{}", diagnostic);
+    assert!(diagnostic.contains("\"rendered\":\"error[E0384]: cannot assign twice to immutable variable `x`\\n"), "{}", diagnostic);
+}
+
+#[test]
+fn nothing_is_written_for_a_program_without_errors() {
+    let output = oxido(&["check", "--error-format=json", &example("mutability_and_shadowing/mutability_pass.rs")]);
+    assert_eq!(output.status, 0);
+    assert_eq!(output.stderr, "");
+}
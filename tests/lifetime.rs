@@ -0,0 +1,63 @@
+mod common;
+
+use common::{errors, example, oxido, oxido_source};
+
+#[test]
+fn borrow_outliving_its_value_is_rejected() {
+    let output = oxido(&["check", &example("lifetime/lifetime_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0597]: `x` does not live long enough at 6:13",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("- `x` dropped here while still borrowed"), "{}", output.stderr);
+    assert!(output.stderr.contains("- borrow later used here"), "{}", output.stderr);
+}
+
+#[test]
+fn borrow_within_the_lifetime_of_its_value_is_accepted() {
+    let output = oxido(&["check", &example("lifetime/lifetime_pass.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+}
+
+#[test]
+fn returned_reference_without_a_named_lifetime_is_rejected() {
+    let output = oxido(&["check", &example("function_lifetime_without_control_flow/wocf_function_missing_lifetime_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0106]: missing lifetime specifier at 2:8",
+        "error: aborting due to previous error",
+    ]);
+}
+
+#[test]
+fn undeclared_lifetime_in_a_signature_is_rejected() {
+    let output = oxido_source(&["check"], "
+fn first<'a>(x: &'a str, y: &'b str) -> &'a str {
+    x
+}
+
+fn main() {
+}");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0261]: use of undeclared lifetime name `'b` at 2:4",
+        "error: aborting due to previous error",
+    ]);
+}
+
+#[test]
+fn result_of_a_call_lives_as_long_as_the_arguments_of_its_lifetime() {
+    let output = oxido(&["check", &example("function_lifetime_without_control_flow/wocf_function_explicit_lifetime_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0597]: `string2` does not live long enough at 12:48",
+        "error: aborting due to previous error",
+    ]);
+    for path in ["wocf_function_explicit_lifetime_pass.rs", "wocf_function_explicit_lifetime_pass_literal_generic.rs",
+        "wocf_function_explicit_lifetime_pass_literal_static.rs"] {
+        let output = oxido(&["run", &example(&format!("function_lifetime_without_control_flow/{}", path))]);
+        assert_eq!(output.status, 0, "{}: {}", path, output.stderr);
+        assert_eq!(output.stdout, "second is second\nThe first string is first\n", "{}", path);
+    }
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::{errors, example, oxido};
+
+#[test]
+fn loop_without_break_has_a_value_of_any_type() {
+    let output = oxido(&["run", &example("loop/loop_without_break.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "4\n");
+}
+
+#[test]
+fn while_loop_without_break_has_no_value() {
+    let output = oxido(&["check", &example("loop/while_without_break_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), ["error[E0308]: mismatched types at 1:4", "error: aborting due to previous error"]);
+    assert!(output.stderr.contains("expected `i64`, found `()`"), "{}", output.stderr);
+}
+
+#[test]
+fn loops_are_left_with_values_and_labels() {
+    let output = oxido(&["run", &example("loop/loop_break_value.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "The result is 20\nEnd count = 2\n2!\n0!\nhello\n");
+}
+
+#[test]
+fn breaks_with_values_of_other_types_or_outside_loops_are_rejected() {
+    let output = oxido(&["check", &example("loop/loop_break_value_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0308]: mismatched types at 6:9",
+        "error[E0571]: `break` with value from a `while` loop at 9:9",
+        "error[E0268]: `break` outside of a loop at 11:5",
+        "error: aborting due to 3 previous errors",
+    ]);
+    assert!(output.stderr.contains("^^^^^^^^^^ expected `i64`, found `bool`"), "{}", output.stderr);
+}
+
+#[test]
+fn return_leaves_the_loops_and_blocks_it_is_within() {
+    let output = oxido(&["run", &example("loop/loop_return.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "7 8 9 72\n");
+}
+
+#[test]
+fn branch_that_is_never_left_takes_the_type_of_the_other() {
+    let output = oxido(&["check", &example("loop/loop_never_coercion_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), ["error[E0308]: mismatched types at 4:19", "error: aborting due to previous error"]);
+    assert!(output.stderr.contains("expected `i64`, found `String`"), "{}", output.stderr);
+}
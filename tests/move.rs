@@ -0,0 +1,27 @@
+mod common;
+
+use common::{example, oxido};
+
+#[test]
+fn moving_in_a_loop_is_reported_as_moved_in_the_previous_iteration() {
+    let output = oxido(&["check", &example("move/move_in_loop_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.contains("error[E0382]: use of moved value: `s`"), "{}", output.stderr);
+    assert!(output.stderr.contains("^ value moved here, in previous iteration of loop"), "{}", output.stderr);
+}
+
+#[test]
+fn moving_in_a_loop_after_assigning_again_runs() {
+    let output = oxido(&["run", &example("move/move_in_loop_reassigned.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "took first\ntook again\n");
+}
+
+#[test]
+fn value_moved_on_one_branch_cannot_be_used_after_it() {
+    let output = oxido(&["check", &example("move/move_on_branch_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.contains("error[E0382]: use of moved value: `s`"), "{}", output.stderr);
+    assert!(output.stderr.contains("- value moved here"), "{}", output.stderr);
+    assert!(output.stderr.contains("^ value used here after move"), "{}", output.stderr);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::{errors, example, oxido, oxido_source};
+
+#[test]
+fn immutable_variable_assigned_twice_is_rejected() {
+    for path in ["mutability_and_shadowing/immutability_error.rs", "mutability_and_shadowing/typed_immutability_error.rs"] {
+        let output = oxido(&["check", &example(path)]);
+        assert_eq!(output.status, 1);
+        assert_eq!(errors(&output.stderr), [
+            "error[E0384]: cannot assign twice to immutable variable `x` at 4:5",
+            "error: aborting due to previous error",
+        ], "{}", path);
+        assert!(output.stderr.contains("= help: consider making this binding mutable: `mut x`"), "{}", output.stderr);
+    }
+}
+
+#[test]
+fn mutable_variable_can_be_assigned_again() {
+    let output = oxido(&["run", &example("mutability_and_shadowing/mutability_pass.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+}
+
+#[test]
+fn immutable_variable_borrowed_mutably_is_rejected() {
+    let output = oxido_source(&["check"], "
+fn main() {
+    let s = string_from(\"a\");
+    let r = &mut s;
+}");
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0596]: cannot borrow `s` as mutable, as it is not declared as mutable at 4:13",
+        "error: aborting due to previous error",
+    ]);
+    assert!(output.stderr.contains("= help: consider making this binding mutable: `mut s`"), "{}", output.stderr);
+}
+
+#[test]
+fn writing_through_a_shared_reference_is_rejected() {
+    let output = oxido(&["check", &example("borrow/borrow_modify_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error[E0596]: cannot borrow `*some_string` as mutable, as it is behind a `&` reference at 8:14",
+        "error: aborting due to previous error",
+    ]);
+}
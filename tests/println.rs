@@ -0,0 +1,28 @@
+mod common;
+
+use common::oxido_source;
+
+fn run(body: &str) -> common::Output {
+    oxido_source(&["run"], &format!("fn main() {{\n{}\n}}", body))
+}
+
+#[test]
+fn placeholders_print_their_arguments_in_order() {
+    let output = run("let s = string_from(\"s\");\n    println(\"{} {:?} {} {:?} {{}}\", 1, true, s, s);");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "1 true s \"s\" {}\n");
+}
+
+#[test]
+fn escapes_in_string_literals_are_replaced_by_their_characters() {
+    let output = run(r#"println("a\nb {{}}\t\\ \"q\" \u{48}\u{e9}\0.");"#);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "a\nb {}\t\\ \"q\" H\u{e9}\0.\n");
+}
+
+#[test]
+fn escape_of_a_code_point_that_is_not_a_character_is_reported() {
+    let output = run(r#"println("\u{D800}");"#);
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.starts_with("error: invalid unicode character escape `\\u{D800}`"), "{}", output.stderr);
+}
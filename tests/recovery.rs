@@ -0,0 +1,57 @@
+mod common;
+
+use common::{errors, example, oxido, oxido_source};
+
+#[test]
+fn missing_semicolon_is_expected_before_the_next_statement() {
+    let output = oxido(&["check", &example("syntax/missing_semicolon_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error: expected `;`, found `let` at 2:14",
+        "error: expected an expression, found `;` at 3:13",
+        "error: aborting due to 2 previous errors",
+    ]);
+    assert!(output.stderr.contains("2 |     let x = 5\n  |              ^ expected `;`\n3 |     let y = ;\n  |     --- unexpected token"),
+        "{}", output.stderr);
+}
+
+#[test]
+fn errors_after_a_syntax_error_are_reported_with_it() {
+    let output = oxido(&["check", &example("syntax/multiple_syntax_errors_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), [
+        "error: expected an expression, found `;` at 2:16",
+        "error: expected `;`, found `let` at 5:25",
+        "error: expected an expression, found `;` at 6:17",
+        "error: expected `;`, found `a` at 12:14",
+        "error[E0308]: mismatched types at 16:4",
+        "error: aborting due to 5 previous errors",
+    ]);
+}
+
+#[test]
+fn program_with_syntax_errors_is_not_run() {
+    let output = oxido(&["run", &example("syntax/missing_semicolon_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(output.stdout, "");
+    assert!(output.stderr.contains("error: aborting due to 2 previous errors"), "{}", output.stderr);
+}
+
+#[test]
+fn characters_standing_in_for_blanked_source_are_rejected() {
+    for c in ['\u{1}', '\u{80}', '\u{FFFF}', '\u{10FFFF}'] {
+        let output = oxido_source(&["check"], &format!("fn main() {{\n    let x = {};\n}}\n", c));
+        assert_eq!(output.status, 1, "{:?}", c);
+        assert_eq!(errors(&output.stderr), [
+            format!("error: unknown start of token: {} at 2:13", c.escape_unicode()),
+            String::from("error: aborting due to previous error"),
+        ]);
+    }
+}
+
+#[test]
+fn characters_standing_in_for_blanked_source_are_allowed_in_strings_and_comments() {
+    let output = oxido_source(&["run"], "fn main() {\n    // \u{FFFF}\n    println(\"{}\", \"a\u{1}b\");\n}\n");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "a\u{1}b\n");
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use common::oxido_source;
+
+// Runs a program that is expected to be accepted, and gives what it printed.
+fn run(source: &str) -> String {
+    let output = oxido_source(&["run"], source);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    output.stdout
+}
+
+#[test]
+fn reference_taken_in_a_block_outlives_the_block() {
+    assert_eq!(run("
+fn main() {
+    let s = string_from(\"kept\");
+    let r = { &s };
+    println(\"{}\", *r);
+}"), "kept\n");
+}
+
+#[test]
+fn reference_taken_in_a_branch_outlives_the_branch() {
+    assert_eq!(run("
+fn main() {
+    let c = false;
+    let x = 1;
+    let y = 2;
+    let r = if c { &x } else { &y };
+    println(\"{}\", *r);
+}"), "2\n");
+}
+
+#[test]
+fn reference_assigned_in_a_block_outlives_the_block() {
+    assert_eq!(run("
+fn main() {
+    let x = 3;
+    let r;
+    {
+        r = &x;
+    }
+    println(\"{}\", *r);
+}"), "3\n");
+}
+
+#[test]
+fn reference_assigned_in_a_loop_outlives_the_loop() {
+    assert_eq!(run("
+fn main() {
+    let x = 4;
+    let y = 5;
+    let mut r = &y;
+    let mut n = 0;
+    while n < 3 {
+        r = &x;
+        n += 1;
+    }
+    println(\"{}\", *r);
+}"), "4\n");
+}
+
+#[test]
+fn assigning_through_a_mutable_reference_writes_to_the_borrowed_variable() {
+    assert_eq!(run("
+fn set(x : &mut i64, value : i64) {
+    *x = value;
+}
+
+fn main() {
+    let mut y = 1;
+    set(&mut y, 42);
+    let r = &mut y;
+    *r = *r + 1;
+    let s = string_from(\"behind\");
+    let t = &s;
+    let u = &t;
+    println(\"{} {} {}\", y, **u, len(*t));
+}"), "43 behind 6\n");
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use common::{example, oxido};
+
+#[test]
+fn diagnostics_are_laid_out_as_rustc_does() {
+    let output = oxido(&["check", "--color=never", &example("borrow/borrow_dangle_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(output.stderr.replace(&example(""), ""), "\
+error[E0505]: cannot move out of `s` because it is borrowed
+ --> borrow/borrow_dangle_error.rs:6:14
+  |
+3 |     let r1 = &s;
+  |              -- borrow of `s` occurs here
+...
+6 |     let s1 = s;
+  |              ^ move out of `s` occurs here
+7 |
+8 |     println(\"{}\", r2);
+  |                   -- borrow later used here
+
+error: aborting due to previous error
+");
+}
+
+#[test]
+fn gutter_is_as_wide_as_the_largest_line_number() {
+    let output = oxido(&["check", "--color=never", &example("borrow/borrow_multiple_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.contains(&format!("\
+  --> {}:11:14
+   |
+10 |     let m1 = & m;
+   |              --- immutable borrow occurs here
+11 |     let m2 = &mut m;
+", example("borrow/borrow_multiple_error.rs"))), "{}", output.stderr);
+}
+
+#[test]
+fn colors_are_only_added_when_asked_for() {
+    let colored = oxido(&["check", "--color=always", &example("borrow/borrow_dangle_error.rs")]);
+    assert!(colored.stderr.starts_with("\x1b[1;31merror\x1b[0m\x1b[1;31m[E0505]\x1b[0m"), "{:?}", colored.stderr);
+    let plain = oxido(&["check", "--color=never", &example("borrow/borrow_dangle_error.rs")]);
+    assert!(!plain.stderr.contains('\x1b'), "{:?}", plain.stderr);
+}
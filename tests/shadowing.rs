@@ -0,0 +1,26 @@
+mod common;
+
+use common::{example, oxido, oxido_source};
+
+#[test]
+fn shadowing_binding_sees_the_one_it_shadows() {
+    let output = oxido(&["run", &example("mutability_and_shadowing/repeat_name_shadow_pass.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "The value of x in the inner scope is: 12\nThe value of x is: 6\n\
+        There are spaces     here.\nThe space is of length 3.\n");
+}
+
+#[test]
+fn shadowed_variable_keeps_its_value_until_the_end_of_its_scope() {
+    let output = oxido_source(&["run", "--trace-drops"], "
+fn main() {
+    let s = string_from(\"first\");
+    let r = &s;
+    let s = string_from(\"second\");
+    println(\"{} {}\", *r, s);
+}");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "first second\n");
+    let dropped: Vec<&str> = output.stderr.lines().filter_map(|line| line.split(" at heap address").next()).collect();
+    assert_eq!(dropped, ["[drop] freed String \"second\"", "[drop] freed String \"first\""]);
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::{example, oxido, oxido_source};
+
+#[test]
+fn expression_spanning_lines_is_located_from_its_start_to_its_end() {
+    let output = oxido_source(&["check", "--error-format=json"], "fn main() {
+    let n : i64 = if true {
+        1
+    } else {
+        2
+    } + false;
+}");
+    assert_eq!(output.status, 1);
+    let diagnostic = output.stderr.lines().next().unwrap();
+    assert!(diagnostic.contains("\"byte_start\":30,\"byte_end\":86,\"line_start\":2,\"line_end\":6,\"column_start\":19,\"column_end\":14,\
+        \"is_primary\":true"), "{}", diagnostic);
+}
+
+#[test]
+fn variable_is_dropped_at_the_closing_brace_of_its_block() {
+    let output = oxido(&["check", "--error-format=json", &example("lifetime/lifetime_error.rs")]);
+    assert_eq!(output.status, 1);
+    let diagnostic = output.stderr.lines().next().unwrap();
+    assert!(diagnostic.contains("\"byte_start\":188,\"byte_end\":190,\"line_start\":6,\"line_end\":6,\"column_start\":13,\"column_end\":15,\
+        \"is_primary\":true"), "{}", diagnostic);
+    assert!(diagnostic.contains("\"byte_start\":220,\"byte_end\":221,\"line_start\":7,\"line_end\":7,\"column_start\":5,\"column_end\":6,\
+        \"is_primary\":false,\"text\":[{\"text\":\"    }                     // -+       |\",\"highlight_start\":5,\"highlight_end\":6}],\
+        \"label\":\"`x` dropped here while still borrowed\""), "{}", diagnostic);
+}
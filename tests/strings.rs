@@ -0,0 +1,23 @@
+mod common;
+
+use common::oxido_source;
+
+#[test]
+fn string_is_pushed_onto_in_place_and_freed_once() {
+    let output = oxido_source(&["run", "--trace-drops"], "
+fn shout(s : &mut String) {
+    push_str(s, \"!\");
+}
+
+fn main() {
+    let mut s = string_from(\"hello\");
+    push_str(s, \", world\");
+    shout(&mut s);
+    let view = as_str(s);
+    println(\"{} {} {}\", view, len(s), len(view));
+}");
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "hello, world! 13 13\n");
+    assert_eq!(output.stderr.matches("[drop] freed String \"hello, world!\"").count(), 1, "{}", output.stderr);
+    assert_eq!(output.stderr.lines().count(), 1, "{}", output.stderr);
+}
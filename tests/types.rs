@@ -0,0 +1,38 @@
+mod common;
+
+use common::{errors, example, oxido, oxido_source};
+
+#[test]
+fn elided_lifetimes_are_left_out_of_types() {
+    let output = oxido(&["check", &example("borrow/borrow_modify_error_param.rs")]);
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.contains("^^ expected `&mut String`, found `&String`"), "{}", output.stderr);
+}
+
+#[test]
+fn named_lifetimes_are_kept_in_types() {
+    let output = oxido_source(&["check"], "\
+fn change<'a>(s : &'a mut String) { }
+fn main() {
+    let s = string_from(\"a\");
+    change(&s);
+}
+");
+    assert_eq!(output.status, 1);
+    assert!(output.stderr.contains("^^ expected `&'a mut String`, found `&String`"), "{}", output.stderr);
+}
+
+#[test]
+fn return_leaves_the_function_from_within_branches() {
+    let output = oxido(&["run", &example("function/function_early_return.rs")]);
+    assert_eq!(output.status, 0, "{}", output.stderr);
+    assert_eq!(output.stdout, "-1 0 1 8\n");
+}
+
+#[test]
+fn returned_value_is_checked_against_the_return_type() {
+    let output = oxido(&["check", &example("function/function_early_return_error.rs")]);
+    assert_eq!(output.status, 1);
+    assert_eq!(errors(&output.stderr), ["error[E0308]: mismatched types at 3:16", "error: aborting due to previous error"]);
+    assert!(output.stderr.contains("^^^^^ expected `i64`, found `bool`"), "{}", output.stderr);
+}